@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+pub struct Args {
+    flags: Vec<String>,
+    options: HashMap<String, String>,
+    values: Vec<String>,
+}
+
+impl Args {
+    pub fn parse(tokens: &[String], flags: &[&str], options: &[&str]) -> Result<Self, String> {
+        Self::parse_tokens(tokens, flags, options, false)
+    }
+
+    pub fn parse_query(
+        tokens: &[String],
+        flags: &[&str],
+        options: &[&str],
+    ) -> Result<Self, String> {
+        Self::parse_tokens(tokens, flags, options, true)
+    }
+
+    fn parse_tokens(
+        tokens: &[String],
+        flags: &[&str],
+        options: &[&str],
+        query: bool,
+    ) -> Result<Self, String> {
+        let mut args = Self::default();
+        let mut tokens = tokens.iter();
+
+        while let Some(token) = tokens.next() {
+            if token == "--" {
+                args.values.extend(tokens.cloned());
+                break;
+            }
+
+            if flags.contains(&token.as_str()) {
+                args.flags.push(token.clone());
+            } else if options.contains(&token.as_str()) {
+                match tokens.next() {
+                    Some(value) => {
+                        args.options.insert(token.clone(), value.clone());
+                    }
+                    _ => return Err(format!("Missing value for option '{token}'.")),
+                }
+            } else if token.len() > 1
+                && token.starts_with('-')
+                && (!query || args.values.is_empty())
+            {
+                return Err(format!("Unknown option '{token}'."));
+            } else {
+                args.values.push(token.clone());
+            }
+        }
+
+        Ok(args)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Args, String> {
+        let tokens = shlex::split(line).unwrap();
+        Args::parse(&tokens, &["-r", "--committed"], &["--limit"])
+    }
+
+    #[test]
+    fn parses_flags_options_and_values() {
+        let args = parse("-r --limit 10 notes \"My Documents/todo.txt\"").unwrap();
+
+        assert!(args.flag("-r"));
+        assert!(!args.flag("--committed"));
+        assert_eq!(args.option("--limit"), Some("10"));
+        assert_eq!(args.values(), ["notes", "My Documents/todo.txt"]);
+    }
+
+    #[test]
+    fn keeps_everything_after_a_double_dash() {
+        let args = parse("-r -- --limit -x -").unwrap();
+
+        assert!(args.flag("-r"));
+        assert_eq!(args.option("--limit"), None);
+        assert_eq!(args.values(), ["--limit", "-x", "-"]);
+    }
+
+    #[test]
+    fn rejects_unknown_options_and_missing_values() {
+        assert_eq!(parse("--fuzzy 1").unwrap_err(), "Unknown option '--fuzzy'.");
+        assert_eq!(
+            parse("hello --limit").unwrap_err(),
+            "Missing value for option '--limit'."
+        );
+        assert_eq!(parse("-").unwrap().values(), ["-"]);
+    }
+
+    #[test]
+    fn keeps_unknown_dashes_in_a_query() {
+        let tokens = shlex::split("fox -quick --limit 5 -\"lazy dog\"").unwrap();
+        let args = Args::parse_query(&tokens, &["--committed"], &["--limit"]).unwrap();
+
+        assert_eq!(args.option("--limit"), Some("5"));
+        assert_eq!(args.values(), ["fox", "-quick", "-lazy dog"]);
+
+        let tokens = shlex::split("-quick fox").unwrap();
+        assert!(Args::parse_query(&tokens, &[], &[]).is_err());
+    }
+}
@@ -1,8 +1,112 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::output::Format;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 pub struct Cli {
     #[arg(long)]
     pub path: String,
+
+    #[arg(long)]
+    pub federate: Vec<String>,
+
+    #[arg(long, value_enum, default_value_t = Protocol::Shell)]
+    pub protocol: Protocol,
+
+    #[arg(long)]
+    pub script: Option<String>,
+
+    #[arg(long)]
+    pub trigrams: bool,
+
+    #[arg(long, global = true, value_enum, default_value_t = Format::Json)]
+    pub format: Format,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Protocol {
+    Shell,
+    Jsonrpc,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    Add {
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    Remove {
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    Search {
+        #[arg(required_unless_present = "cursor")]
+        query: Option<String>,
+        #[arg(long)]
+        limit: Option<usize>,
+        #[arg(long)]
+        offset: Option<usize>,
+        #[arg(long, conflicts_with_all = ["query", "offset", "limit"])]
+        cursor: Option<String>,
+        #[arg(long)]
+        snippets: Option<usize>,
+        #[arg(long)]
+        snippet_length: Option<usize>,
+        #[arg(long)]
+        fuzzy: Option<u8>,
+        #[arg(long)]
+        at: Option<String>,
+    },
+    Regex {
+        pattern: String,
+        #[arg(long)]
+        content: bool,
+        #[arg(long)]
+        limit: Option<usize>,
+        #[arg(long)]
+        offset: Option<usize>,
+        #[arg(long)]
+        snippets: Option<usize>,
+        #[arg(long)]
+        snippet_length: Option<usize>,
+        #[arg(long)]
+        at: Option<String>,
+    },
+    Grep {
+        text: String,
+        #[arg(long)]
+        limit: Option<usize>,
+        #[arg(long)]
+        offset: Option<usize>,
+        #[arg(long)]
+        snippets: Option<usize>,
+        #[arg(long)]
+        snippet_length: Option<usize>,
+        #[arg(long)]
+        at: Option<String>,
+    },
+    List {
+        #[arg(long)]
+        at: Option<String>,
+    },
+    Clear,
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: String,
+    },
+    #[cfg(unix)]
+    Daemon {
+        #[arg(long)]
+        socket: Option<String>,
+    },
+    #[cfg(unix)]
+    Client {
+        #[arg(long)]
+        socket: Option<String>,
+        request: Option<String>,
+    },
 }
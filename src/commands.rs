@@ -0,0 +1,263 @@
+use std::{fs, process::ExitCode};
+
+use file_search::{
+    Error, FileSearch, FileSearchMode, FileSearchWriteTransaction, FileSnippetOptions,
+};
+use serde::Serialize;
+
+use crate::{
+    cli::Command,
+    output::{self, Format, Record},
+};
+
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+
+const EXIT_NO_MATCHES: u8 = 1;
+pub const EXIT_FAILURE: u8 = 2;
+const EXIT_FILE_ERROR: u8 = 3;
+
+struct Page<'a> {
+    offset: usize,
+    limit: usize,
+    cursor: Option<&'a str>,
+    snippets: FileSnippetOptions,
+    fuzzy: Option<u8>,
+    mode: FileSearchMode,
+}
+
+impl Page<'_> {
+    fn new(
+        offset: Option<usize>,
+        limit: Option<usize>,
+        snippets: Option<usize>,
+        snippet_length: Option<usize>,
+        mode: FileSearchMode,
+    ) -> Self {
+        let defaults = FileSnippetOptions::default();
+
+        Self {
+            offset: offset.unwrap_or(0),
+            limit: limit.unwrap_or(DEFAULT_PAGE_SIZE),
+            cursor: None,
+            snippets: FileSnippetOptions {
+                count: snippets.unwrap_or(defaults.count),
+                max_length: snippet_length.unwrap_or(defaults.max_length),
+            },
+            fuzzy: None,
+            mode,
+        }
+    }
+}
+
+pub fn run(searcher: &FileSearch, command: Command, format: Format) -> ExitCode {
+    match command {
+        Command::Add { paths } => run_add(searcher, &paths),
+        Command::Remove { paths } => run_remove(searcher, &paths),
+        Command::Search {
+            query,
+            limit,
+            offset,
+            cursor,
+            snippets,
+            snippet_length,
+            fuzzy,
+            at,
+        } => {
+            let page = Page {
+                cursor: cursor.as_deref(),
+                fuzzy,
+                ..Page::new(
+                    offset,
+                    limit,
+                    snippets,
+                    snippet_length,
+                    FileSearchMode::Query,
+                )
+            };
+
+            run_search(searcher, query.as_deref(), page, at.as_deref(), format)
+        }
+        Command::Regex {
+            pattern,
+            content,
+            limit,
+            offset,
+            snippets,
+            snippet_length,
+            at,
+        } => {
+            let mode = if content {
+                FileSearchMode::RegexContent
+            } else {
+                FileSearchMode::Regex
+            };
+            let page = Page::new(offset, limit, snippets, snippet_length, mode);
+
+            run_search(searcher, Some(&pattern), page, at.as_deref(), format)
+        }
+        Command::Grep {
+            text,
+            limit,
+            offset,
+            snippets,
+            snippet_length,
+            at,
+        } => {
+            let page = Page::new(
+                offset,
+                limit,
+                snippets,
+                snippet_length,
+                FileSearchMode::Substring,
+            );
+
+            run_search(searcher, Some(&text), page, at.as_deref(), format)
+        }
+        Command::List { at } => run_list(searcher, at.as_deref(), format),
+        Command::Clear => run_clear(searcher),
+        Command::Serve { .. } => ExitCode::from(EXIT_FAILURE),
+        #[cfg(unix)]
+        Command::Daemon { .. } | Command::Client { .. } => ExitCode::from(EXIT_FAILURE),
+    }
+}
+
+fn run_add(searcher: &FileSearch, paths: &[String]) -> ExitCode {
+    if let Some(code) = check_files(paths) {
+        return code;
+    }
+
+    change_index(searcher, |writer| {
+        for path in paths {
+            writer.add(path)?;
+        }
+
+        Ok(())
+    })
+}
+
+fn run_remove(searcher: &FileSearch, paths: &[String]) -> ExitCode {
+    change_index(searcher, |writer| {
+        for path in paths {
+            writer.remove(path)?;
+        }
+
+        Ok(())
+    })
+}
+
+fn run_search(
+    searcher: &FileSearch,
+    query: Option<&str>,
+    page: Page,
+    at: Option<&str>,
+    format: Format,
+) -> ExitCode {
+    let reader = match at {
+        Some(snapshot) => searcher.open_read_at(snapshot),
+        _ => searcher.open_read(),
+    };
+    let reader = reader.map(|reader| {
+        reader
+            .with_snippets(page.snippets)
+            .with_fuzzy(page.fuzzy)
+            .with_mode(page.mode)
+    });
+    let result = reader.and_then(|reader| match page.cursor {
+        Some(cursor) => reader.search_cursor(cursor),
+        _ => reader.search_page(query.unwrap_or_default(), page.offset, page.limit),
+    });
+
+    match result {
+        Ok(page) => match output::print_page(&page, format) {
+            Ok(()) if page.total == 0 => ExitCode::from(EXIT_NO_MATCHES),
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => fail("Cannot print output.", &error),
+        },
+        Err(error) => fail("Failed to search documents.", &error),
+    }
+}
+
+fn run_list(searcher: &FileSearch, at: Option<&str>, format: Format) -> ExitCode {
+    let reader = match at {
+        Some(snapshot) => searcher.open_read_at(snapshot),
+        _ => searcher.open_read(),
+    };
+
+    match reader.and_then(|reader| reader.list()) {
+        Ok(entries) => print_records(&entries, format),
+        Err(error) => fail("Cannot retrive documents.", &error),
+    }
+}
+
+fn run_clear(searcher: &FileSearch) -> ExitCode {
+    match searcher.open_write().and_then(|writer| writer.clear()) {
+        Ok(summary) => print_json(&summary),
+        Err(error) => fail("Failed to clear documents.", &error),
+    }
+}
+
+fn change_index<F>(searcher: &FileSearch, f: F) -> ExitCode
+where
+    F: FnOnce(&mut FileSearchWriteTransaction) -> Result<(), Error>,
+{
+    let mut writer = match searcher.open_write() {
+        Ok(writer) => writer,
+        Err(error) => return fail("Unable to start write session.", &error),
+    };
+
+    if let Err(error) = f(&mut writer) {
+        if let Err(error) = writer.rollback() {
+            eprintln!("Failed to rollback. {error}");
+        }
+
+        return fail("Failed to change index.", &error);
+    }
+
+    match writer.commit() {
+        Ok(summary) => print_json(&summary),
+        Err(error) => fail("Failed to commit.", &error),
+    }
+}
+
+fn check_files(paths: &[String]) -> Option<ExitCode> {
+    for path in paths {
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => {}
+            Ok(_) => {
+                eprintln!("The path '{path}' is not a file.");
+                return Some(ExitCode::from(EXIT_FILE_ERROR));
+            }
+            Err(error) => {
+                eprintln!("Failed to access file '{path}'. {error}");
+                return Some(ExitCode::from(EXIT_FILE_ERROR));
+            }
+        }
+    }
+
+    None
+}
+
+fn print_json<T: Serialize>(value: &T) -> ExitCode {
+    match serde_json::to_string(value) {
+        Ok(json) => {
+            println!("{json}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("Cannot serialize output. {error}");
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+fn print_records<T: Record>(records: &[T], format: Format) -> ExitCode {
+    match output::print(records, format) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => fail("Cannot print output.", &error),
+    }
+}
+
+fn fail(message: &str, error: &Error) -> ExitCode {
+    eprintln!("{message} {error}");
+    ExitCode::from(EXIT_FAILURE)
+}
@@ -0,0 +1,131 @@
+use file_search::FileSearchReadTransaction;
+use rustyline::{
+    Context, Helper,
+    completion::{Completer, FilenameCompleter, Pair, Quote, escape, extract_word, unescape},
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+};
+
+const COMMANDS: &[&str] = &[
+    "add",
+    "backup",
+    "clear",
+    "collections",
+    "commit",
+    "delete-snapshot",
+    "exit",
+    "export",
+    "federate",
+    "federation",
+    "format",
+    "grep",
+    "help",
+    "import",
+    "list",
+    "regex",
+    "remove",
+    "restore",
+    "rollback",
+    "rollback-to",
+    "savepoint",
+    "search",
+    "snapshot",
+    "snapshots",
+    "source",
+    "status",
+    "use",
+];
+const FILE_COMMANDS: &[&str] = &[
+    "add", "backup", "export", "federate", "import", "restore", "source",
+];
+const ESCAPE_CHAR: Option<char> = Some('\\');
+
+pub struct ShellHelper {
+    files: FilenameCompleter,
+    reader: Option<FileSearchReadTransaction>,
+    added: Vec<String>,
+}
+
+impl ShellHelper {
+    pub fn new() -> Self {
+        Self {
+            files: FilenameCompleter::new(),
+            reader: None,
+            added: Vec::new(),
+        }
+    }
+
+    pub fn set_indexed(&mut self, reader: Option<FileSearchReadTransaction>, added: Vec<String>) {
+        self.reader = reader;
+        self.added = added;
+    }
+
+    fn complete_indexed(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let (start, word) = extract_word(line, pos, ESCAPE_CHAR, is_break_char);
+        let word = unescape(word, ESCAPE_CHAR);
+        let committed = self
+            .reader
+            .as_ref()
+            .and_then(|reader| reader.list().ok())
+            .unwrap_or_default();
+        let candidates = committed
+            .into_iter()
+            .map(|entry| entry.path)
+            .chain(self.added.iter().cloned())
+            .filter(|path| path.starts_with(word.as_ref()))
+            .map(|path| Pair {
+                display: path.clone(),
+                replacement: escape(path, ESCAPE_CHAR, is_break_char, Quote::None),
+            })
+            .collect();
+
+        (start, candidates)
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let head = line[..pos].trim_start();
+
+        let Some((command, _)) = head.split_once(char::is_whitespace) else {
+            let candidates = COMMANDS
+                .iter()
+                .filter(|name| name.starts_with(head))
+                .map(|name| Pair {
+                    display: name.to_string(),
+                    replacement: format!("{name} "),
+                })
+                .collect();
+
+            return Ok((pos - head.len(), candidates));
+        };
+
+        match command {
+            "remove" => Ok(self.complete_indexed(line, pos)),
+            command if FILE_COMMANDS.contains(&command) => self.files.complete(line, pos, ctx),
+            _ => Ok((pos, Vec::new())),
+        }
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+fn is_break_char(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '"' | '\'' | '\\')
+}
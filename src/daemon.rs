@@ -0,0 +1,191 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    sync::Arc,
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+
+use file_search::{Error, ErrorSource, FileSearch};
+
+use crate::service::{Service, ServiceError, ServiceResult};
+
+pub const SOCKET_FILENAME: &str = "file-search.sock";
+
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+enum DaemonRequest {
+    Search {
+        query: String,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    },
+    List,
+    Stats,
+    Add {
+        path: String,
+    },
+    Remove {
+        path: String,
+    },
+    Commit,
+    Rollback,
+}
+
+#[derive(Serialize)]
+struct DaemonError {
+    kind: &'static str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct DaemonResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Box<RawValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<DaemonError>,
+}
+
+pub struct Daemon {
+    service: Arc<Service>,
+}
+
+impl Daemon {
+    pub fn new(searcher: FileSearch) -> Self {
+        Self {
+            service: Arc::new(Service::new(searcher)),
+        }
+    }
+
+    pub fn listen(self, socket: &Path) -> Result<(), Error> {
+        if socket.exists() {
+            if UnixStream::connect(socket).is_ok() {
+                return Err(Error {
+                    source: ErrorSource::Io,
+                    message: format!("A daemon is already listening on '{}'.", socket.display()),
+                });
+            }
+
+            fs::remove_file(socket)?;
+        }
+
+        let listener = UnixListener::bind(socket)?;
+        println!("Listening on {}", socket.display());
+
+        for (connection, stream) in listener.incoming().enumerate() {
+            match stream {
+                Ok(stream) => {
+                    let service = Arc::clone(&self.service);
+                    let session = format!("connection-{connection}");
+
+                    thread::spawn(move || {
+                        if let Err(error) = Self::handle_connection(&service, &session, stream) {
+                            eprintln!("Connection closed with error. {error}");
+                        }
+
+                        if let Err(error) = service.close(&session) {
+                            eprintln!("{}", error.into_message());
+                        }
+                    });
+                }
+                Err(error) => eprintln!("Failed to accept connection. {error}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_connection(service: &Service, session: &str, stream: UnixStream) -> io::Result<()> {
+        let mut writer = stream.try_clone()?;
+
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let result = match serde_json::from_str::<DaemonRequest>(&line) {
+                Ok(request) => Self::dispatch(service, session, request),
+                Err(error) => Err(ServiceError::Invalid(format!("Invalid request. {error}"))),
+            };
+            let response = match result {
+                Ok(result) => DaemonResponse {
+                    result: Some(result),
+                    error: None,
+                },
+                Err(error) => DaemonResponse {
+                    result: None,
+                    error: Some(DaemonError {
+                        kind: error.kind(),
+                        message: error.into_message(),
+                    }),
+                },
+            };
+
+            serde_json::to_writer(&mut writer, &response)?;
+            writeln!(writer)?;
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn dispatch(
+        service: &Service,
+        session: &str,
+        request: DaemonRequest,
+    ) -> ServiceResult<Box<RawValue>> {
+        match request {
+            DaemonRequest::Search {
+                query,
+                offset,
+                limit,
+            } => Self::to_value(service.search(&query, offset, limit)),
+            DaemonRequest::List => Self::to_value(service.list()),
+            DaemonRequest::Stats => Self::to_value(service.stats()),
+            DaemonRequest::Add { path } => Self::to_value(service.add(session, &path)),
+            DaemonRequest::Remove { path } => Self::to_value(service.remove(session, &path)),
+            DaemonRequest::Commit => Self::to_value(service.commit(session)),
+            DaemonRequest::Rollback => Self::to_value(service.rollback(session)),
+        }
+    }
+
+    fn to_value<T: Serialize>(result: ServiceResult<T>) -> ServiceResult<Box<RawValue>> {
+        serde_json::value::to_raw_value(&result?)
+            .map_err(|error| ServiceError::Internal(format!("Cannot serialize response. {error}")))
+    }
+}
+
+pub fn run_client(socket: &Path, request: Option<&str>) -> Result<(), Error> {
+    let stream = UnixStream::connect(socket)?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut send = |line: &str| -> Result<(), Error> {
+        let mut response = String::new();
+        writeln!(writer, "{line}")?;
+        writer.flush()?;
+        reader.read_line(&mut response)?;
+        print!("{response}");
+        Ok(())
+    };
+
+    match request {
+        Some(request) => send(request),
+        _ => {
+            for line in io::stdin().lock().lines() {
+                let line = line?;
+
+                if !line.trim().is_empty() {
+                    send(&line)?;
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
@@ -11,6 +11,8 @@ pub enum ErrorSource {
     Io,
     Redb,
     Tantivy,
+    Json,
+    Search,
 }
 
 #[derive(Debug)]
@@ -118,12 +120,23 @@ impl From<TransactionError> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Error {
+            source: ErrorSource::Json,
+            message: value.to_string(),
+        }
+    }
+}
+
 impl Display for ErrorSource {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             ErrorSource::Io => write!(f, "io"),
             ErrorSource::Redb => write!(f, "redb"),
             ErrorSource::Tantivy => write!(f, "tantivy"),
+            ErrorSource::Json => write!(f, "json"),
+            ErrorSource::Search => write!(f, "search"),
         }
     }
 }
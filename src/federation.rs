@@ -0,0 +1,69 @@
+use tantivy::{Index, IndexReader, ReloadPolicy, TantivyError, directory::MmapDirectory};
+
+use crate::{
+    error::{Error, ErrorSource},
+    search::{self, FileSearch, FileSearchPage},
+};
+
+pub struct FederatedIndex {
+    name: String,
+    reader: IndexReader,
+}
+
+impl FederatedIndex {
+    pub fn open(path: &str) -> Result<Self, Error> {
+        let directory = MmapDirectory::open(path)?;
+
+        if !Index::exists(&directory).map_err(TantivyError::from)? {
+            return Err(Error {
+                source: ErrorSource::Search,
+                message: format!("No index exists at '{path}'."),
+            });
+        }
+
+        let index = search::open_index(directory)?;
+
+        Ok(Self {
+            name: path.into(),
+            reader: index
+                .reader_builder()
+                .reload_policy(ReloadPolicy::OnCommitWithDelay)
+                .try_into()?,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+pub fn search_federated(
+    local: &FileSearch,
+    members: &[FederatedIndex],
+    query: &str,
+    offset: usize,
+    limit: usize,
+) -> Result<FileSearchPage, Error> {
+    let name = local.root().to_string_lossy();
+    let mut searchers = vec![(
+        (name.as_ref(), Some(local.collection())),
+        local.open_read()?.searcher(),
+    )];
+
+    searchers.extend(
+        members
+            .iter()
+            .map(|member| ((member.name.as_str(), None), member.reader.searcher())),
+    );
+
+    search::search_merged(
+        &searchers,
+        query,
+        offset,
+        limit,
+        |entry, (name, collection)| {
+            entry.index = Some(name.to_string());
+            entry.collection = collection.map(str::to_string);
+        },
+    )
+}
@@ -0,0 +1,340 @@
+use std::ops::{Bound, Range, RangeBounds};
+
+use regex::Regex;
+use tantivy::{
+    query_grammar::{Occur, UserInputAst, UserInputBound, UserInputLeaf},
+    tokenizer::TextAnalyzer,
+};
+
+use crate::{error::Error, query};
+
+pub struct HighlightToken {
+    pub position: usize,
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+pub enum QueryMatcher {
+    Clause(Vec<QueryMatcher>),
+    Phrase {
+        terms: Vec<(usize, String)>,
+        slop: u32,
+        prefix: bool,
+    },
+    Fuzzy {
+        term: String,
+        distance: u8,
+    },
+    Regex(Regex),
+    Set(Vec<String>),
+    Range {
+        lower: Bound<String>,
+        upper: Bound<String>,
+    },
+    Nothing,
+}
+
+impl QueryMatcher {
+    pub fn new(
+        ast: UserInputAst,
+        field: &str,
+        analyzer: &mut TextAnalyzer,
+        fuzzy: Option<u8>,
+    ) -> Result<Self, Error> {
+        Self::from_ast(ast, field, analyzer, fuzzy)
+    }
+
+    pub fn highlight(&self, tokens: &[HighlightToken]) -> Vec<bool> {
+        let mut marked = vec![false; tokens.len()];
+        self.mark(tokens, &mut marked);
+        marked
+    }
+
+    fn from_ast(
+        ast: UserInputAst,
+        field: &str,
+        analyzer: &mut TextAnalyzer,
+        fuzzy: Option<u8>,
+    ) -> Result<Self, Error> {
+        match ast {
+            UserInputAst::Clause(clauses) => clauses
+                .into_iter()
+                .filter(|(occur, _)| *occur != Some(Occur::MustNot))
+                .map(|(_, ast)| Self::from_ast(ast, field, analyzer, fuzzy))
+                .collect::<Result<_, _>>()
+                .map(Self::Clause),
+            UserInputAst::Boost(ast, _) => Self::from_ast(*ast, field, analyzer, fuzzy),
+            UserInputAst::Leaf(leaf) => Self::from_leaf(*leaf, field, analyzer, fuzzy),
+        }
+    }
+
+    fn from_leaf(
+        leaf: UserInputLeaf,
+        field: &str,
+        analyzer: &mut TextAnalyzer,
+        fuzzy: Option<u8>,
+    ) -> Result<Self, Error> {
+        let targets = |name: &Option<String>| name.as_deref().is_none_or(|name| name == field);
+
+        match leaf {
+            UserInputLeaf::Literal(literal) if targets(&literal.field_name) => {
+                let mut terms = Vec::new();
+
+                analyzer
+                    .token_stream(&literal.phrase)
+                    .process(&mut |token| terms.push((token.position, token.text.clone())));
+
+                match query::fuzziness(&literal, terms.len(), fuzzy)? {
+                    Some(distance) => Ok(Self::Fuzzy {
+                        term: terms.swap_remove(0).1,
+                        distance,
+                    }),
+                    _ => Ok(Self::Phrase {
+                        terms,
+                        slop: literal.slop,
+                        prefix: literal.prefix,
+                    }),
+                }
+            }
+            UserInputLeaf::Set { field, elements } if targets(&field) => Ok(Self::Set(
+                elements
+                    .iter()
+                    .map(|element| element.to_lowercase())
+                    .collect(),
+            )),
+            UserInputLeaf::Range {
+                field,
+                lower,
+                upper,
+            } if targets(&field) => Ok(Self::Range {
+                lower: Self::bound(lower),
+                upper: Self::bound(upper),
+            }),
+            _ => Ok(Self::Nothing),
+        }
+    }
+
+    fn bound(bound: UserInputBound) -> Bound<String> {
+        match bound {
+            UserInputBound::Inclusive(value) => Bound::Included(value.to_lowercase()),
+            UserInputBound::Exclusive(value) => Bound::Excluded(value.to_lowercase()),
+            UserInputBound::Unbounded => Bound::Unbounded,
+        }
+    }
+
+    fn mark(&self, tokens: &[HighlightToken], marked: &mut [bool]) {
+        match self {
+            Self::Clause(children) => {
+                for child in children {
+                    child.mark(tokens, marked);
+                }
+            }
+            Self::Phrase {
+                terms,
+                slop,
+                prefix,
+            } => Self::mark_phrase(terms, *slop as usize, *prefix, tokens, marked),
+            Self::Fuzzy { term, distance } => {
+                let term: Vec<_> = term.chars().collect();
+
+                for (token, marked) in tokens.iter().zip(marked.iter_mut()) {
+                    *marked |= edit_distance(&term, &token.text, *distance as usize);
+                }
+            }
+            Self::Regex(regex) => {
+                for (token, marked) in tokens.iter().zip(marked.iter_mut()) {
+                    *marked |= regex.is_match(&token.text);
+                }
+            }
+            Self::Set(elements) => {
+                for (token, marked) in tokens.iter().zip(marked.iter_mut()) {
+                    *marked |= elements.contains(&token.text);
+                }
+            }
+            Self::Range { lower, upper } => {
+                let range = (
+                    lower.as_ref().map(String::as_str),
+                    upper.as_ref().map(String::as_str),
+                );
+
+                for (token, marked) in tokens.iter().zip(marked.iter_mut()) {
+                    *marked |= range.contains(&token.text.as_str());
+                }
+            }
+            Self::Nothing => {}
+        }
+    }
+
+    fn mark_phrase(
+        terms: &[(usize, String)],
+        slop: usize,
+        prefix: bool,
+        tokens: &[HighlightToken],
+        marked: &mut [bool],
+    ) {
+        let Some(((first_position, first), rest)) = terms.split_first() else {
+            return;
+        };
+        let last = terms.len() - 1;
+        let matches = |index: usize, term: &str, token: &HighlightToken| {
+            if prefix && index == last {
+                token.text.starts_with(term)
+            } else {
+                token.text == term
+            }
+        };
+
+        for start in 0..tokens.len() {
+            if !matches(0, first, &tokens[start]) {
+                continue;
+            }
+
+            let mut matched = vec![start];
+            let mut budget = slop;
+            let mut previous = (tokens[start].position, *first_position);
+
+            for (index, (position, term)) in rest.iter().enumerate() {
+                let expected = previous.0 + position.saturating_sub(previous.1);
+                let next = matched[matched.len() - 1] + 1;
+                let found = tokens[next..]
+                    .iter()
+                    .enumerate()
+                    .take_while(|(_, token)| token.position <= expected + budget)
+                    .find(|(_, token)| {
+                        token.position >= expected && matches(index + 1, term, token)
+                    });
+
+                match found {
+                    Some((offset, token)) => {
+                        budget -= token.position - expected;
+                        previous = (token.position, *position);
+                        matched.push(next + offset);
+                    }
+                    _ => {
+                        matched.clear();
+                        break;
+                    }
+                }
+            }
+
+            for index in matched {
+                marked[index] = true;
+            }
+        }
+    }
+}
+
+fn edit_distance(term: &[char], text: &str, distance: usize) -> bool {
+    let text: Vec<_> = text.chars().collect();
+
+    if term.len().abs_diff(text.len()) > distance {
+        return false;
+    }
+
+    let mut rows = vec![(0..=text.len()).collect::<Vec<_>>()];
+
+    for (i, a) in term.iter().enumerate() {
+        let mut row = vec![i + 1; text.len() + 1];
+
+        for (j, b) in text.iter().enumerate() {
+            let above = &rows[i];
+
+            row[j + 1] = (above[j] + usize::from(a != b))
+                .min(above[j + 1] + 1)
+                .min(row[j] + 1);
+
+            if i > 0 && j > 0 && *a == text[j - 1] && term[i - 1] == *b {
+                row[j + 1] = row[j + 1].min(rows[i - 1][j - 1] + 1);
+            }
+        }
+
+        if row.iter().all(|cost| *cost > distance) {
+            return false;
+        }
+
+        rows.push(row);
+    }
+
+    rows[term.len()][text.len()] <= distance
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::tokenizer::{LowerCaser, SimpleTokenizer};
+
+    use super::*;
+
+    const TEXT: &str = "The quick fox and the quick brown fox";
+
+    fn marked(query: &str, fuzzy: Option<u8>) -> Vec<String> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .build();
+        let matcher = QueryMatcher::new(
+            query::parse(query).unwrap(),
+            "content",
+            &mut analyzer,
+            fuzzy,
+        )
+        .unwrap();
+        let mut tokens = Vec::new();
+
+        analyzer.token_stream(TEXT).process(&mut |token| {
+            tokens.push(HighlightToken {
+                position: token.position,
+                range: token.offset_from..token.offset_to,
+                text: token.text.clone(),
+            })
+        });
+
+        let marked = matcher.highlight(&tokens);
+
+        tokens
+            .into_iter()
+            .zip(marked)
+            .filter(|(_, marked)| *marked)
+            .map(|(token, _)| format!("{}@{}", token.text, token.position))
+            .collect()
+    }
+
+    #[test]
+    fn phrase_marks_adjacent_terms_only() {
+        assert_eq!(marked("\"quick fox\"", None), ["quick@1", "fox@2"]);
+    }
+
+    #[test]
+    fn slop_allows_gaps_between_terms() {
+        assert_eq!(
+            marked("\"quick fox\"~1", None),
+            ["quick@1", "fox@2", "quick@5", "fox@7"]
+        );
+    }
+
+    #[test]
+    fn prefix_matches_the_last_term() {
+        assert_eq!(marked("\"quick br\"*", None), ["quick@5", "brown@6"]);
+    }
+
+    #[test]
+    fn negated_terms_are_not_marked() {
+        assert_eq!(marked("fox -quick", None), ["fox@2", "fox@7"]);
+    }
+
+    #[test]
+    fn fuzzy_terms_match_within_distance() {
+        assert_eq!(marked("qiuck", Some(1)), ["quick@1", "quick@5"]);
+        assert!(marked("qiuck", None).is_empty());
+    }
+
+    #[test]
+    fn edit_distance_counts_transpositions_once() {
+        let term: Vec<_> = "fox".chars().collect();
+
+        assert!(edit_distance(&term, "ofx", 1));
+        assert!(edit_distance(&term, "fx", 1));
+        assert!(edit_distance(&term, "box", 1));
+        assert!(!edit_distance(&term, "ofx", 0));
+        assert!(!edit_distance(&term, "foxes", 1));
+        assert!(!edit_distance(&term, "oxf", 1));
+    }
+}
@@ -0,0 +1,14 @@
+pub mod error;
+pub mod federation;
+mod highlight;
+mod query;
+pub mod search;
+
+pub use error::{Error, ErrorSource};
+pub use federation::{FederatedIndex, search_federated};
+pub use search::{
+    FileBackupEntry, FileBackupManifest, FileChangeSummary, FileCollectionEntry, FileDocumentEntry,
+    FileExportEntry, FileMatch, FileMatchColumn, FileSearch, FileSearchBuilder, FileSearchEntry,
+    FileSearchMode, FileSearchPage, FileSearchReadTransaction, FileSearchStats,
+    FileSearchWriteTransaction, FileSnapshotEntry, FileSnippet, FileSnippetOptions,
+};
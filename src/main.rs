@@ -1,21 +1,90 @@
+mod args;
 mod cli;
-mod error;
-mod search;
+mod commands;
+mod completion;
+#[cfg(unix)]
+mod daemon;
+mod output;
+mod rpc;
+mod server;
+mod service;
 mod shell;
 
-use std::{error::Error, path::Path};
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
 use clap::Parser;
+use file_search::FileSearch;
 
-use crate::{cli::Cli, search::FileSearch, shell::Shell};
+use crate::{
+    cli::{Cli, Command, Protocol},
+    rpc::RpcShell,
+    server::Server,
+    shell::Shell,
+};
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> Result<ExitCode, Box<dyn Error>> {
     let cli = Cli::parse();
     let path = Path::new(&cli.path);
-    let searcher = FileSearch::create(path)?;
-    let mut stdin_handler = Shell::new(searcher);
+
+    #[cfg(unix)]
+    if let Some(Command::Client { socket, request }) = &cli.command {
+        let socket = socket_path(path, socket.as_deref());
+        daemon::run_client(&socket, request.as_deref())?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let searcher = FileSearch::builder(path).trigrams(cli.trigrams).open()?;
+
+    match cli.command {
+        Some(Command::Serve { listen }) => {
+            Server::new(searcher).listen(&listen)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        #[cfg(unix)]
+        Some(Command::Daemon { socket }) => {
+            let socket = socket_path(path, socket.as_deref());
+            daemon::Daemon::new(searcher).listen(&socket)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        #[cfg(unix)]
+        Some(Command::Client { .. }) => {}
+        Some(command) => return Ok(commands::run(&searcher, command, cli.format)),
+        _ => {}
+    }
+
+    if cli.protocol == Protocol::Jsonrpc {
+        RpcShell::new(searcher).watch()?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let mut stdin_handler = Shell::new(searcher, cli.format);
+
+    for path in cli.federate.iter() {
+        stdin_handler.federate(path)?;
+    }
+
+    if let Some(script) = &cli.script {
+        if let Err(error) = stdin_handler.run_script(script) {
+            eprintln!("{error}");
+            return Ok(ExitCode::from(commands::EXIT_FAILURE));
+        }
+
+        return Ok(ExitCode::SUCCESS);
+    }
 
     stdin_handler.watch();
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(unix)]
+fn socket_path(path: &Path, socket: Option<&str>) -> PathBuf {
+    match socket {
+        Some(socket) => PathBuf::from(socket),
+        _ => path.join(daemon::SOCKET_FILENAME),
+    }
 }
@@ -0,0 +1,333 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, IsTerminal},
+    ops::Range,
+};
+
+use clap::ValueEnum;
+use file_search::{Error, FileDocumentEntry, FileSearchEntry, FileSearchPage};
+use serde::Serialize;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Human,
+    Json,
+    Ndjson,
+    Csv,
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.to_possible_value() {
+            Some(value) => write!(f, "{}", value.get_name()),
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Self::from_str(name, true).map_err(|_| {
+            format!("Unknown output format '{name}'. Expected one of: human, json, ndjson, csv.")
+        })
+    }
+}
+
+pub trait Record: Serialize {
+    fn columns() -> &'static [&'static str];
+
+    fn fields(&self) -> Vec<String>;
+
+    fn table_columns() -> &'static [&'static str] {
+        Self::columns()
+    }
+
+    fn table_cells(&self) -> Vec<String> {
+        self.fields()
+    }
+}
+
+impl Record for FileSearchEntry {
+    fn columns() -> &'static [&'static str] {
+        &["index", "collection", "path", "score", "matches", "snippet"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        let matches: Vec<_> = sorted_matches(self)
+            .into_iter()
+            .map(|(term, range)| format!("{term}:{}-{}", range.start, range.end))
+            .collect();
+
+        vec![
+            self.index.clone().unwrap_or_default(),
+            self.collection.clone().unwrap_or_default(),
+            self.path.clone(),
+            self.score.to_string(),
+            matches.join(";"),
+            plain_snippet(self),
+        ]
+    }
+
+    fn table_columns() -> &'static [&'static str] {
+        &["SCORE", "PATH", "SNIPPET"]
+    }
+
+    fn table_cells(&self) -> Vec<String> {
+        let path = match (&self.index, &self.collection) {
+            (Some(index), _) => format!("[{index}] {}", self.path),
+            (_, Some(collection)) => format!("[{collection}] {}", self.path),
+            _ => self.path.clone(),
+        };
+
+        vec![format!("{:.3}", self.score), path, snippet(self)]
+    }
+}
+
+impl Record for FileDocumentEntry {
+    fn columns() -> &'static [&'static str] {
+        &["path", "epoch", "hash"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.path.clone(),
+            self.epoch.to_string(),
+            self.hash.to_string(),
+        ]
+    }
+
+    fn table_columns() -> &'static [&'static str] {
+        &["PATH", "MODIFIED", "HASH"]
+    }
+
+    fn table_cells(&self) -> Vec<String> {
+        vec![
+            self.path.clone(),
+            format_epoch(self.epoch),
+            format!("{:016x}", self.hash),
+        ]
+    }
+}
+
+pub fn print<T: Record>(records: &[T], format: Format) -> Result<(), Error> {
+    match format {
+        Format::Human => print_table(records),
+        Format::Json => println!("{}", serde_json::to_string(records)?),
+        Format::Ndjson => {
+            for record in records {
+                println!("{}", serde_json::to_string(record)?);
+            }
+        }
+        Format::Csv => {
+            println!("{}", csv_row(T::columns().iter().map(|c| c.to_string())));
+
+            for record in records {
+                println!("{}", csv_row(record.fields()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn print_page(page: &FileSearchPage, format: Format) -> Result<(), Error> {
+    if format == Format::Json {
+        println!("{}", serde_json::to_string(page)?);
+        return Ok(());
+    }
+
+    print(&page.entries, format)?;
+
+    if page.total == 0 {
+        return Ok(());
+    }
+
+    let summary = match page.entries.len() {
+        0 => format!("No results shown of {}.", page.total),
+        shown => format!(
+            "Showing {}-{} of {} results.",
+            page.offset + 1,
+            page.offset + shown,
+            page.total
+        ),
+    };
+
+    if format == Format::Human {
+        println!("{summary}");
+
+        if let Some(cursor) = &page.cursor {
+            println!("Next page: search --cursor {cursor}");
+        }
+    } else {
+        eprintln!("{summary}");
+
+        if let Some(cursor) = &page.cursor {
+            eprintln!("Next page: search --cursor {cursor}");
+        }
+    }
+
+    Ok(())
+}
+
+fn print_table<T: Record>(records: &[T]) {
+    if records.is_empty() {
+        println!("No entries found.");
+        return;
+    }
+
+    let columns = T::table_columns();
+    let rows: Vec<_> = records.iter().map(Record::table_cells).collect();
+    let mut widths: Vec<_> = columns
+        .iter()
+        .map(|column| column.chars().count())
+        .collect();
+
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header: Vec<_> = columns.iter().map(|column| column.to_string()).collect();
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let mut line = String::new();
+
+        for (index, cell) in row.iter().enumerate() {
+            if index + 1 == row.len() {
+                line.push_str(cell);
+            } else {
+                let padding = widths[index] - cell.chars().count();
+                line.push_str(cell);
+                line.push_str(&" ".repeat(padding + 2));
+            }
+        }
+
+        println!("{}", line.trim_end());
+    }
+}
+
+fn csv_row<I: IntoIterator<Item = String>>(fields: I) -> String {
+    let fields: Vec<_> = fields
+        .into_iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+
+    fields.join(",")
+}
+
+fn plain_snippet(entry: &FileSearchEntry) -> String {
+    let snippets: Vec<_> = entry
+        .snippets
+        .iter()
+        .map(|snippet| {
+            snippet
+                .text
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+
+    snippets.join(" … ")
+}
+
+fn sorted_matches(entry: &FileSearchEntry) -> Vec<(&str, &Range<usize>)> {
+    let mut matches: Vec<_> = entry
+        .fragments
+        .iter()
+        .flat_map(|(term, ranges)| ranges.iter().map(move |range| (term.as_str(), range)))
+        .collect();
+
+    matches.sort_by_key(|(term, range)| (range.start, range.end, *term));
+    matches
+}
+
+fn snippet(entry: &FileSearchEntry) -> String {
+    let highlight = io::stdout().is_terminal();
+    let snippets: Vec<_> = entry
+        .snippets
+        .iter()
+        .map(|snippet| {
+            let mut text = String::new();
+            let mut cursor = 0;
+
+            for range in &snippet.highlights {
+                if range.start < cursor {
+                    continue;
+                }
+
+                text.push_str(&snippet.text[cursor..range.start]);
+
+                if highlight {
+                    text.push_str(&format!(
+                        "\x1b[1;31m{}\x1b[0m",
+                        &snippet.text[range.clone()]
+                    ));
+                } else {
+                    text.push_str(&format!("[{}]", &snippet.text[range.clone()]));
+                }
+
+                cursor = range.end;
+            }
+
+            text.push_str(&snippet.text[cursor..]);
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        })
+        .collect();
+
+    snippets.join(" … ")
+}
+
+fn format_epoch(epoch: u128) -> String {
+    let seconds = (epoch / 1000) as i64;
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_row_quotes_only_fields_that_need_it() {
+        let row = csv_row([
+            "plain".to_string(),
+            "a,b".to_string(),
+            "say \"hi\"".to_string(),
+            "two\r\nlines".to_string(),
+        ]);
+
+        assert_eq!(row, "plain,\"a,b\",\"say \"\"hi\"\"\",\"two\r\nlines\"");
+    }
+
+    #[test]
+    fn format_epoch_prints_utc_dates() {
+        assert_eq!(format_epoch(0), "1970-01-01 00:00:00");
+        assert_eq!(format_epoch(951_782_400_000), "2000-02-29 00:00:00");
+        assert_eq!(format_epoch(1_735_689_599_999), "2024-12-31 23:59:59");
+        assert_eq!(format_epoch(4_102_444_800_000), "2100-01-01 00:00:00");
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::BTreeSet;
+
+use regex::Regex;
+use tantivy::{
+    Term,
+    query::{
+        AllQuery, BooleanQuery, BoostQuery, EmptyQuery, FuzzyTermQuery, Query, QueryParser,
+        TermQuery,
+    },
+    query_grammar::{self, Delimiter, Occur, UserInputAst, UserInputLeaf, UserInputLiteral},
+    schema::{Field, IndexRecordOption},
+    tokenizer::TextAnalyzer,
+};
+
+use crate::error::{Error, ErrorSource};
+
+pub const MAX_FUZZY_DISTANCE: u8 = 2;
+
+pub fn parse(query: &str) -> Result<UserInputAst, Error> {
+    let mut ast = query_grammar::parse_query(query).map_err(|_| Error {
+        source: ErrorSource::Search,
+        message: format!("Cannot parse query '{query}'."),
+    })?;
+
+    split_fuzzy_suffixes(&mut ast);
+    Ok(ast)
+}
+
+fn split_fuzzy_suffixes(ast: &mut UserInputAst) {
+    match ast {
+        UserInputAst::Clause(clauses) => {
+            for (_, ast) in clauses {
+                split_fuzzy_suffixes(ast);
+            }
+        }
+        UserInputAst::Boost(ast, _) => split_fuzzy_suffixes(ast),
+        UserInputAst::Leaf(leaf) => {
+            let UserInputLeaf::Literal(literal) = leaf.as_mut() else {
+                return;
+            };
+
+            if literal.delimiter != Delimiter::None || literal.slop > 0 {
+                return;
+            }
+
+            let Some((phrase, slop)) = literal.phrase.rsplit_once('~') else {
+                return;
+            };
+
+            if let (false, Ok(slop)) = (phrase.is_empty(), slop.parse()) {
+                literal.phrase = phrase.into();
+                literal.slop = slop;
+            }
+        }
+    }
+}
+
+pub fn trigrams(text: &str, field: Field) -> Box<dyn Query> {
+    let chars: Vec<_> = text.chars().collect();
+    let trigrams: BTreeSet<String> = chars
+        .windows(3)
+        .map(|window| window.iter().collect())
+        .collect();
+
+    if trigrams.is_empty() {
+        return Box::new(AllQuery);
+    }
+
+    Box::new(BooleanQuery::new(
+        trigrams
+            .into_iter()
+            .map(|trigram| {
+                let term = Term::from_field_text(field, &trigram);
+                let query: Box<dyn Query> =
+                    Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                (Occur::Must, query)
+            })
+            .collect(),
+    ))
+}
+
+pub fn regex(pattern: &str, anchored: bool) -> Result<Regex, Error> {
+    let invalid = |error: regex::Error| Error {
+        source: ErrorSource::Search,
+        message: format!("Invalid regular expression '{pattern}'. {error}"),
+    };
+    let regex = Regex::new(pattern).map_err(invalid)?;
+
+    if !anchored {
+        return Ok(regex);
+    }
+
+    Regex::new(&format!("^(?:{pattern})$")).map_err(invalid)
+}
+
+pub fn fuzziness(
+    literal: &UserInputLiteral,
+    terms: usize,
+    fuzzy: Option<u8>,
+) -> Result<Option<u8>, Error> {
+    if terms != 1 || literal.prefix {
+        return Ok(None);
+    }
+
+    let distance = match (literal.slop, fuzzy) {
+        (0, fuzzy) if literal.delimiter == Delimiter::None => fuzzy,
+        (0, _) => None,
+        (slop, _) => Some(u8::try_from(slop).unwrap_or(u8::MAX)),
+    };
+
+    match distance {
+        Some(distance) if distance > MAX_FUZZY_DISTANCE => Err(Error {
+            source: ErrorSource::Search,
+            message: format!(
+                "Fuzzy distance {distance} is not supported. Use at most {MAX_FUZZY_DISTANCE}."
+            ),
+        }),
+        Some(0) => Ok(None),
+        distance => Ok(distance),
+    }
+}
+
+pub fn build(
+    parser: &QueryParser,
+    ast: UserInputAst,
+    (field, name): (Field, &str),
+    analyzer: &mut TextAnalyzer,
+    fuzzy: Option<u8>,
+) -> Result<Box<dyn Query>, Error> {
+    match build_ast(parser, ast, (field, name), analyzer, fuzzy)? {
+        Some(query) => Ok(query),
+        _ => Ok(Box::new(EmptyQuery)),
+    }
+}
+
+fn build_ast(
+    parser: &QueryParser,
+    ast: UserInputAst,
+    target: (Field, &str),
+    analyzer: &mut TextAnalyzer,
+    fuzzy: Option<u8>,
+) -> Result<Option<Box<dyn Query>>, Error> {
+    match ast {
+        UserInputAst::Clause(clauses) => {
+            let mut subqueries = Vec::new();
+
+            for (occur, ast) in clauses {
+                if let Some(query) = build_ast(parser, ast, target, analyzer, fuzzy)? {
+                    subqueries.push((occur.unwrap_or(Occur::Should), query));
+                }
+            }
+
+            if subqueries.is_empty() {
+                return Ok(None);
+            }
+
+            if subqueries.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+                subqueries.push((Occur::Should, Box::new(AllQuery)));
+            }
+
+            Ok(Some(Box::new(BooleanQuery::new(subqueries))))
+        }
+        UserInputAst::Boost(ast, boost) => Ok(build_ast(parser, *ast, target, analyzer, fuzzy)?
+            .map(|query| Box::new(BoostQuery::new(query, boost as f32)) as _)),
+        UserInputAst::Leaf(leaf) => match *leaf {
+            UserInputLeaf::Literal(literal)
+                if literal
+                    .field_name
+                    .as_deref()
+                    .is_none_or(|name| name == target.1) =>
+            {
+                let mut terms = Vec::new();
+
+                analyzer
+                    .token_stream(&literal.phrase)
+                    .process(&mut |token| terms.push(token.text.clone()));
+
+                match (fuzziness(&literal, terms.len(), fuzzy)?, terms.pop()) {
+                    (_, None) => Ok(None),
+                    (Some(distance), Some(text)) => {
+                        let term = Term::from_field_text(target.0, &text);
+                        let exact = TermQuery::new(term.clone(), IndexRecordOption::WithFreqs);
+                        let variants = FuzzyTermQuery::new(term, distance, true);
+
+                        Ok(Some(Box::new(BooleanQuery::new(vec![
+                            (Occur::Should, Box::new(exact) as Box<dyn Query>),
+                            (Occur::Should, Box::new(variants)),
+                        ]))))
+                    }
+                    _ => leaf_query(parser, UserInputLeaf::Literal(literal)),
+                }
+            }
+            leaf => leaf_query(parser, leaf),
+        },
+    }
+}
+
+fn leaf_query(parser: &QueryParser, leaf: UserInputLeaf) -> Result<Option<Box<dyn Query>>, Error> {
+    Ok(Some(parser.build_query_from_user_input_ast(
+        UserInputAst::Leaf(Box::new(leaf)),
+    )?))
+}
@@ -0,0 +1,230 @@
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Value, value::RawValue};
+
+use file_search::{Error, FileSearch};
+
+use crate::service::{Service, ServiceError, ServiceResult};
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const INTERNAL_ERROR: i32 = -32603;
+const NOT_FOUND: i32 = -32001;
+const CONFLICT: i32 = -32002;
+
+const SESSION: &str = "stdin";
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    #[serde(default, deserialize_with = "present")]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    query: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct PathParams {
+    path: String,
+}
+
+#[derive(Serialize)]
+struct RpcError {
+    code: i32,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Box<RawValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    fn success(id: Value, result: Box<RawValue>) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    fn failure(id: Value, code: i32, message: String) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(RpcError { code, message }),
+        }
+    }
+}
+
+pub struct RpcShell {
+    service: Service,
+}
+
+impl RpcShell {
+    pub fn new(searcher: FileSearch) -> Self {
+        Self {
+            service: Service::new(searcher),
+        }
+    }
+
+    pub fn watch(&self) -> Result<(), Error> {
+        let mut stdout = io::stdout().lock();
+
+        for line in io::stdin().lock().lines() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let reply = match serde_json::from_str::<Value>(&line) {
+                Ok(Value::Array(batch)) if !batch.is_empty() => {
+                    let responses: Vec<_> = batch
+                        .into_iter()
+                        .filter_map(|request| self.handle_value(request))
+                        .collect();
+
+                    if responses.is_empty() {
+                        None
+                    } else {
+                        Some(serde_json::to_string(&responses)?)
+                    }
+                }
+                Ok(request) => self
+                    .handle_value(request)
+                    .map(|response| serde_json::to_string(&response))
+                    .transpose()?,
+                Err(error) => Some(serde_json::to_string(&RpcResponse::failure(
+                    Value::Null,
+                    PARSE_ERROR,
+                    format!("Parse error. {error}"),
+                ))?),
+            };
+
+            if let Some(reply) = reply {
+                writeln!(stdout, "{reply}")?;
+                stdout.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_value(&self, value: Value) -> Option<RpcResponse> {
+        let request: RpcRequest = match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(error) => {
+                return Some(RpcResponse::failure(
+                    Value::Null,
+                    INVALID_REQUEST,
+                    format!("Invalid request. {error}"),
+                ));
+            }
+        };
+
+        if request.jsonrpc != "2.0" {
+            return Some(RpcResponse::failure(
+                request.id.unwrap_or_default(),
+                INVALID_REQUEST,
+                "Invalid request. Only JSON-RPC 2.0 is supported.".into(),
+            ));
+        }
+
+        let notification = request.id.is_none();
+        let response = self.handle_request(request);
+
+        (!notification).then_some(response)
+    }
+
+    fn handle_request(&self, request: RpcRequest) -> RpcResponse {
+        let id = request.id.unwrap_or_default();
+        let params = request.params.unwrap_or(Value::Null);
+        let result = match request.method.as_str() {
+            "search" => Self::parse_params(params).map(|params: SearchParams| {
+                Self::to_value(
+                    self.service
+                        .search(&params.query, params.offset, params.limit),
+                )
+            }),
+            "list" => Ok(Self::to_value(self.service.list())),
+            "stats" => Ok(Self::to_value(self.service.stats())),
+            "add" => Self::parse_params(params)
+                .map(|params: PathParams| Self::to_value(self.service.add(SESSION, &params.path))),
+            "remove" => Self::parse_params(params).map(|params: PathParams| {
+                Self::to_value(self.service.remove(SESSION, &params.path))
+            }),
+            "commit" => Ok(Self::to_value(self.service.commit(SESSION))),
+            "rollback" => Ok(Self::to_value(self.service.rollback(SESSION))),
+            method => {
+                return RpcResponse::failure(
+                    id,
+                    METHOD_NOT_FOUND,
+                    format!("Method '{method}' not found."),
+                );
+            }
+        };
+
+        match result {
+            Ok(Ok(result)) => RpcResponse::success(id, result),
+            Ok(Err(error)) => {
+                let code = match error {
+                    ServiceError::Invalid(_) => INVALID_PARAMS,
+                    ServiceError::NotFound(_) => NOT_FOUND,
+                    ServiceError::Conflict(_) => CONFLICT,
+                    ServiceError::Internal(_) => INTERNAL_ERROR,
+                };
+
+                RpcResponse::failure(id, code, error.into_message())
+            }
+            Err(message) => RpcResponse::failure(id, INVALID_PARAMS, message),
+        }
+    }
+
+    fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, String> {
+        serde_json::from_value(params).map_err(|error| format!("Invalid params. {error}"))
+    }
+
+    fn to_value<T: Serialize>(result: ServiceResult<T>) -> ServiceResult<Box<RawValue>> {
+        serde_json::value::to_raw_value(&result?)
+            .map_err(|error| ServiceError::Internal(format!("Cannot serialize response. {error}")))
+    }
+}
+
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_ids_are_not_notifications() {
+        let request: RpcRequest =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":null,"method":"list"}"#).unwrap();
+        assert_eq!(request.id, Some(Value::Null));
+
+        let request: RpcRequest =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","method":"list"}"#).unwrap();
+        assert_eq!(request.id, None);
+    }
+}
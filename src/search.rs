@@ -1,33 +1,48 @@
 use std::{
     any::type_name,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fmt::Debug,
     fs,
+    io::{BufRead, Read, Write},
     ops::Range,
-    path::Path,
-    time::UNIX_EPOCH,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use bincode::{Decode, Encode, decode_from_slice, encode_to_vec};
 use redb::{
-    Database, ReadTransaction, ReadableTable, TableDefinition, TypeName, Value as RedbValue,
-    WriteTransaction,
+    Database, Key, ReadTransaction, ReadableTable, ReadableTableMetadata, TableDefinition,
+    TableError, TypeName, Value as RedbValue, WriteTransaction,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tantivy::{
-    Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term,
-    collector::TopDocs,
-    directory::MmapDirectory,
-    indexer::IndexWriterOptions,
-    query::QueryParser,
-    schema::{self, Field, Schema, Value as TantivyValue},
+    Index, IndexMeta, IndexReader, IndexSettings, IndexWriter, ReloadPolicy, Searcher,
+    TantivyDocument, TantivyError, Term,
+    collector::{Count, DocSetCollector, TopDocs},
+    directory::{Directory, MmapDirectory},
+    index::SegmentComponent,
+    indexer::{IndexWriterOptions, UserOperation},
+    query::{
+        AllQuery, Bm25StatisticsProvider, BooleanQuery, Occur, Query, QueryParser, RegexQuery,
+        TermQuery,
+    },
+    schema::{
+        self, Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions,
+        Value as TantivyValue,
+    },
+    tokenizer::{NgramTokenizer, TextAnalyzer},
 };
-use xxhash_rust::xxh3::xxh3_64;
+use xxhash_rust::xxh3::{Xxh3, xxh3_64};
 
-use crate::error::Error;
+use crate::{
+    error::{Error, ErrorSource},
+    highlight::{HighlightToken, QueryMatcher},
+    query,
+};
 
 #[derive(Debug, Decode, Encode, PartialEq, Clone)]
-pub struct FileStateEntry {
+struct FileStateEntry {
     epoch: u128,
     hash: u64,
 }
@@ -39,42 +54,381 @@ pub struct FileDocumentEntry {
     pub hash: u64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileExportEntry {
+    pub path: String,
+    pub epoch: u128,
+    pub hash: u64,
+    pub content: String,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct FileSearchEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
     pub path: String,
     pub score: f32,
     pub fragments: HashMap<String, Vec<Range<usize>>>,
+    pub matches: Vec<FileMatch>,
+    pub snippets: Vec<FileSnippet>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMatch {
+    pub term: String,
+    pub range: Range<usize>,
+    pub line: usize,
+    pub column: FileMatchColumn,
+    pub line_text: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMatchColumn {
+    pub bytes: usize,
+    pub chars: usize,
+    pub utf16: usize,
+}
+
+struct FileLineCursor<'a> {
+    content: &'a str,
+    line: usize,
+    start: usize,
+}
+
+impl<'a> FileLineCursor<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            content,
+            line: 1,
+            start: 0,
+        }
+    }
+
+    fn locate(&mut self, term: &str, range: Range<usize>) -> FileMatch {
+        while let Some(index) = self.content[self.start..range.start].find('\n') {
+            self.line += 1;
+            self.start += index + 1;
+        }
+
+        let end = self.content[self.start..]
+            .find('\n')
+            .map_or(self.content.len(), |index| self.start + index);
+        let prefix = &self.content[self.start..range.start];
+
+        FileMatch {
+            term: term.into(),
+            line: self.line,
+            column: FileMatchColumn {
+                bytes: prefix.len(),
+                chars: prefix.chars().count(),
+                utf16: prefix.encode_utf16().count(),
+            },
+            line_text: self.content[self.start..end].trim_end_matches('\r').into(),
+            range,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSnippet {
+    pub text: String,
+    pub highlights: Vec<Range<usize>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FileSnippetOptions {
+    pub count: usize,
+    pub max_length: usize,
+}
+
+impl Default for FileSnippetOptions {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            max_length: 150,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Decode, Encode)]
+pub enum FileSearchMode {
+    #[default]
+    Query,
+    Regex,
+    RegexContent,
+    Substring,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct FileQueryOptions {
+    snippets: FileSnippetOptions,
+    fuzzy: Option<u8>,
+    mode: FileSearchMode,
+}
+
+struct FileSnippetCandidate {
+    start: usize,
+    stop: usize,
+    score: f32,
+    highlights: Vec<Range<usize>>,
+}
+
+impl FileSnippetCandidate {
+    fn new(start: usize) -> Self {
+        Self {
+            start,
+            stop: start,
+            score: 0.0,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Decode, Encode, PartialEq, Clone)]
+struct FileSnapshotState {
+    created: u128,
+    documents: u64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchPage {
+    pub total: usize,
+    pub offset: usize,
+    pub entries: Vec<FileSearchEntry>,
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Decode, Encode)]
+struct FileSearchCursor {
+    generation: u64,
+    query: String,
+    fuzzy: Option<u8>,
+    mode: FileSearchMode,
+    offset: usize,
+    limit: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileSearchStats {
+    pub documents: u64,
+    pub indexed: u64,
+    pub segments: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileSnapshotEntry {
+    pub name: String,
+    pub created: u128,
+    pub documents: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileCollectionEntry {
+    pub name: String,
+    pub documents: u64,
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileBackupEntry {
+    pub path: String,
+    pub size: u64,
+    pub hash: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileBackupManifest {
+    pub collection: String,
+    pub created: u128,
+    pub documents: u64,
+    pub files: Vec<FileBackupEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileChange {
+    Added,
+    Updated,
+    Touched,
+    Removed,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChangeSummary {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub touched: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl FileChangeSummary {
+    fn from_changes(changes: &BTreeMap<String, FileChange>) -> Self {
+        let mut summary = Self::default();
+
+        for (path, change) in changes {
+            let target = match change {
+                FileChange::Added => &mut summary.added,
+                FileChange::Updated => &mut summary.updated,
+                FileChange::Touched => &mut summary.touched,
+                FileChange::Removed => &mut summary.removed,
+            };
+
+            target.push(path.clone());
+        }
+
+        summary
+    }
 }
 
 const DB_FILENAME: &str = "file_states.redb";
-const STATE_TABLE: TableDefinition<&str, Bincode<FileStateEntry>> =
-    TableDefinition::new("file_states");
+const SNAPSHOTS_DIRNAME: &str = "snapshots";
+const INDEX_META_FILENAME: &str = "meta.json";
+const BACKUP_MANIFEST_FILENAME: &str = "backup.json";
+const RESTORE_DIRNAME: &str = ".restore";
+const RESTORE_OLD_SNAPSHOTS_DIRNAME: &str = "snapshots.old";
+const COLLECTIONS_DIRNAME: &str = "collections";
+const DEFAULT_COLLECTION: &str = "default";
+const PINNED_SEARCHERS: usize = 16;
+const CONSISTENT_STATE_ATTEMPTS: usize = 5;
+const PATH_FIELD: &str = "path";
+const CONTENT_FIELD: &str = "content";
+const TRIGRAMS_FIELD: &str = "trigrams";
+const TRIGRAM_TOKENIZER: &str = "trigram";
+const COLLECTION_TABLE: TableDefinition<&str, u128> = TableDefinition::new("collections");
+
+#[derive(Debug, Clone)]
+struct FileSearchTables {
+    state: String,
+    snapshot: String,
+    snapshot_state: String,
+}
+
+impl FileSearchTables {
+    fn new(collection: &str) -> Self {
+        let prefix = match collection {
+            DEFAULT_COLLECTION => String::new(),
+            _ => format!("{collection}/"),
+        };
+
+        Self {
+            state: format!("{prefix}file_states"),
+            snapshot: format!("{prefix}snapshots"),
+            snapshot_state: format!("{prefix}snapshot_states"),
+        }
+    }
+
+    fn state(&self) -> TableDefinition<'_, &'static str, Bincode<FileStateEntry>> {
+        TableDefinition::new(&self.state)
+    }
+
+    fn snapshot(&self) -> TableDefinition<'_, &'static str, Bincode<FileSnapshotState>> {
+        TableDefinition::new(&self.snapshot)
+    }
+
+    fn snapshot_state(
+        &self,
+    ) -> TableDefinition<'_, (&'static str, &'static str), Bincode<FileStateEntry>> {
+        TableDefinition::new(&self.snapshot_state)
+    }
+}
+
+pub(crate) struct FileSearchStatistics {
+    searchers: Vec<Searcher>,
+}
+
+impl FileSearchStatistics {
+    pub(crate) fn new(searchers: Vec<Searcher>) -> Self {
+        Self { searchers }
+    }
+}
+
+impl Bm25StatisticsProvider for FileSearchStatistics {
+    fn total_num_tokens(&self, field: Field) -> tantivy::Result<u64> {
+        self.searchers.iter().try_fold(0, |total, searcher| {
+            Ok(total + searcher.total_num_tokens(field)?)
+        })
+    }
+
+    fn total_num_docs(&self) -> tantivy::Result<u64> {
+        self.searchers
+            .iter()
+            .try_fold(0, |total, searcher| Ok(total + searcher.total_num_docs()?))
+    }
+
+    fn doc_freq(&self, term: &Term) -> tantivy::Result<u64> {
+        self.searchers
+            .iter()
+            .try_fold(0, |total, searcher| Ok(total + searcher.doc_freq(term)?))
+    }
+}
 
 pub struct FileSearchReadTransaction {
     txn: ReadTransaction,
     reader: IndexReader,
+    tables: FileSearchTables,
     field_path: Field,
     field_content: Field,
+    snapshot: Option<String>,
+    pinned: Arc<Mutex<VecDeque<Searcher>>>,
+    options: FileQueryOptions,
 }
 
 impl FileSearchReadTransaction {
-    pub fn new(
+    fn new(
         txn: ReadTransaction,
         reader: IndexReader,
+        tables: FileSearchTables,
         field_path: Field,
         field_content: Field,
+        snapshot: Option<String>,
+        pinned: Arc<Mutex<VecDeque<Searcher>>>,
     ) -> Self {
         Self {
             txn,
             reader,
+            tables,
             field_path,
             field_content,
+            snapshot,
+            pinned,
+            options: FileQueryOptions::default(),
         }
     }
 
+    pub fn with_snippets(mut self, snippets: FileSnippetOptions) -> Self {
+        self.options.snippets = snippets;
+        self
+    }
+
+    pub fn with_fuzzy(mut self, fuzzy: Option<u8>) -> Self {
+        self.options.fuzzy = fuzzy;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: FileSearchMode) -> Self {
+        self.options.mode = mode;
+        self
+    }
+
     pub fn list(&self) -> Result<Vec<FileDocumentEntry>, Error> {
-        let table = self.txn.open_table(STATE_TABLE)?;
+        if let Some(snapshot) = self.snapshot.as_deref() {
+            return self.list_snapshot(snapshot);
+        }
+
+        let table = match self.txn.open_table(self.tables.state()) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
         let mut result = Vec::new();
 
         for entry in table.iter()? {
@@ -94,78 +448,270 @@ impl FileSearchReadTransaction {
     }
 
     pub fn search(&self, query: &str, limit: Option<usize>) -> Result<Vec<FileSearchEntry>, Error> {
+        search_documents(
+            &self.reader.searcher(),
+            self.field_path,
+            self.field_content,
+            query,
+            limit,
+            None,
+            self.options,
+        )
+    }
+
+    pub fn search_page(
+        &self,
+        query: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<FileSearchPage, Error> {
         let searcher = self.reader.searcher();
-        let index = searcher.index();
-        let query_parser = QueryParser::for_index(index, vec![self.field_content]);
-        let query = query_parser.parse_query(query)?;
-        let mut terms = HashSet::new();
-
-        query.query_terms(&mut |term, _| {
-            if let Some(text) = term.value().as_str() {
-                terms.insert(text.to_string());
-            }
+        let generation = searcher_generation(&searcher);
+
+        self.pin(searcher.clone(), generation);
+        self.search_pinned(&searcher, generation, query, offset, limit, self.options)
+    }
+
+    pub fn search_cursor(&self, cursor: &str) -> Result<FileSearchPage, Error> {
+        let cursor = decode_cursor(cursor)?;
+        let current = self.reader.searcher();
+        let searcher = if searcher_generation(&current) == cursor.generation {
+            Some(current)
+        } else {
+            self.pinned
+                .lock()
+                .map_err(|_| Self::pinned_poisoned())?
+                .iter()
+                .find(|searcher| searcher_generation(searcher) == cursor.generation)
+                .cloned()
+        };
+
+        match searcher {
+            Some(searcher) => self.search_pinned(
+                &searcher,
+                cursor.generation,
+                &cursor.query,
+                cursor.offset,
+                cursor.limit,
+                FileQueryOptions {
+                    fuzzy: cursor.fuzzy,
+                    mode: cursor.mode,
+                    ..self.options
+                },
+            ),
+            _ => Err(Error {
+                source: ErrorSource::Search,
+                message: "The search cursor has expired. Please run the search again.".into(),
+            }),
+        }
+    }
+
+    fn search_pinned(
+        &self,
+        searcher: &Searcher,
+        generation: u64,
+        query: &str,
+        offset: usize,
+        limit: usize,
+        options: FileQueryOptions,
+    ) -> Result<FileSearchPage, Error> {
+        search_window(offset, limit)?;
+
+        let (total, entries) = collect_documents(
+            searcher,
+            (self.field_path, self.field_content),
+            query,
+            (offset, limit),
+            None,
+            options,
+            &[],
+        )?;
+        let next = offset + entries.len();
+        let cursor = (next < total && !entries.is_empty()).then(|| {
+            encode_cursor(&FileSearchCursor {
+                generation,
+                query: query.into(),
+                fuzzy: options.fuzzy,
+                mode: options.mode,
+                offset: next,
+                limit,
+            })
         });
 
-        let collector = TopDocs::with_limit(limit.unwrap_or(100_000));
-        let top_docs = searcher.search(&query, &collector)?;
-        let mut entries = Vec::new();
+        Ok(FileSearchPage {
+            total,
+            offset,
+            entries,
+            cursor: cursor.transpose()?,
+            warning: None,
+        })
+    }
+
+    fn pin(&self, searcher: Searcher, generation: u64) {
+        let Ok(mut pinned) = self.pinned.lock() else {
+            return;
+        };
+
+        pinned.retain(|pinned| searcher_generation(pinned) != generation);
+        pinned.push_front(searcher);
+        pinned.truncate(PINNED_SEARCHERS);
+    }
 
-        for (score, doc_address) in top_docs {
-            let doc: TantivyDocument = searcher.doc(doc_address)?;
-            let mut fragments: HashMap<String, Vec<Range<usize>>> = HashMap::new();
+    fn pinned_poisoned() -> Error {
+        Error {
+            source: ErrorSource::Search,
+            message: "The pinned searchers are unavailable.".into(),
+        }
+    }
 
-            if let Some(content) = Self::get_doc_value(&doc, self.field_content) {
-                let mut tokenizer = index.tokenizer_for_field(self.field_content)?;
-                let mut token_stream = tokenizer.token_stream(content);
+    pub(crate) fn searcher(&self) -> Searcher {
+        self.reader.searcher()
+    }
 
-                while let Some(token) = token_stream.next() {
-                    let token_text = token.text.to_lowercase();
+    pub fn export<W: Write>(&self, mut output: W) -> Result<usize, Error> {
+        let searcher = self.reader.searcher();
+        let documents = self.list()?;
 
-                    if terms.contains(&token_text) {
-                        fragments
-                            .entry(token_text)
-                            .or_default()
-                            .push(token.offset_from..token.offset_to);
-                    }
+        for document in documents.iter() {
+            let term = Term::from_field_text(self.field_path, &document.path);
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+            let content = match top_docs.first() {
+                Some((_, doc_address)) => {
+                    let doc: TantivyDocument = searcher.doc(*doc_address)?;
+                    get_doc_value(&doc, self.field_content)
+                        .unwrap_or_default()
+                        .to_owned()
                 }
-            }
+                _ => {
+                    return Err(Error {
+                        source: ErrorSource::Search,
+                        message: format!("Document '{}' is missing from index.", document.path),
+                    });
+                }
+            };
+            let entry = FileExportEntry {
+                path: document.path.clone(),
+                epoch: document.epoch,
+                hash: document.hash,
+                content,
+            };
 
-            if let Some(path) = Self::get_doc_value(&doc, self.field_path) {
-                entries.push(FileSearchEntry {
-                    score,
-                    fragments,
-                    path: path.into(),
-                });
-            }
+            serde_json::to_writer(&mut output, &entry)?;
+            writeln!(output)?;
+        }
+
+        output.flush()?;
+        Ok(documents.len())
+    }
+
+    pub fn stats(&self) -> Result<FileSearchStats, Error> {
+        let searcher = self.reader.searcher();
+        let documents = match self.txn.open_table(self.tables.state()) {
+            Ok(table) => table.len()?,
+            Err(TableError::TableDoesNotExist(_)) => 0,
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(FileSearchStats {
+            documents,
+            indexed: searcher.num_docs(),
+            segments: searcher.segment_readers().len(),
+        })
+    }
+
+    pub fn snapshots(&self) -> Result<Vec<FileSnapshotEntry>, Error> {
+        let table = match self.txn.open_table(self.tables.snapshot()) {
+            Ok(table) => table,
+            Err(TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+        let mut result = Vec::new();
+
+        for entry in table.iter()? {
+            let (key_guard, value_guard) = entry?;
+            let value = value_guard.value();
+
+            result.push(FileSnapshotEntry {
+                name: key_guard.value().into(),
+                created: value.created,
+                documents: value.documents,
+            });
         }
 
-        Ok(entries)
+        Ok(result)
     }
 
-    fn get_doc_value(doc: &TantivyDocument, field: Field) -> Option<&str> {
-        doc.get_first(field).and_then(|value| value.as_str())
+    fn list_snapshot(&self, snapshot: &str) -> Result<Vec<FileDocumentEntry>, Error> {
+        let table = self.txn.open_table(self.tables.snapshot_state())?;
+        let mut result = Vec::new();
+
+        for entry in table.range((snapshot, "")..)? {
+            let (key_guard, value_guard) = entry?;
+            let (name, path) = key_guard.value();
+
+            if name != snapshot {
+                break;
+            }
+
+            let value = value_guard.value();
+
+            result.push(FileDocumentEntry {
+                path: path.into(),
+                epoch: value.epoch,
+                hash: value.hash,
+            });
+        }
+
+        Ok(result)
     }
 }
 
+struct FileSearchSavepoint {
+    name: String,
+    undo_len: usize,
+    log_len: usize,
+    log_bytes: usize,
+    changes: BTreeMap<String, FileChange>,
+}
+
 pub struct FileSearchWriteTransaction {
     txn: WriteTransaction,
     writer: IndexWriter<TantivyDocument>,
+    tables: FileSearchTables,
     field_path: Field,
     field_content: Field,
+    changes: BTreeMap<String, FileChange>,
+    opened_at: Instant,
+    log: Option<Vec<UserOperation>>,
+    log_bytes: usize,
+    log_budget: usize,
+    undo: Vec<(String, Option<FileStateEntry>)>,
+    savepoints: Vec<FileSearchSavepoint>,
 }
 
 impl FileSearchWriteTransaction {
-    pub fn new(
+    fn new(
         txn: WriteTransaction,
         writer: IndexWriter<TantivyDocument>,
+        tables: FileSearchTables,
         field_path: Field,
         field_content: Field,
+        log_budget: usize,
     ) -> Self {
         Self {
             txn,
             writer,
+            tables,
             field_path,
             field_content,
+            changes: BTreeMap::new(),
+            opened_at: Instant::now(),
+            log: Some(Vec::new()),
+            log_bytes: 0,
+            log_budget,
+            undo: Vec::new(),
+            savepoints: Vec::new(),
         }
     }
 
@@ -179,10 +725,12 @@ impl FileSearchWriteTransaction {
 
                     if state.hash == hash {
                         self.insert_into_state(path, FileStateEntry { epoch, hash })?;
+                        self.track_change(path, FileChange::Touched);
                     } else {
                         self.delete_from_index(path)?;
                         self.insert_into_index(path, content)?;
                         self.insert_into_state(path, FileStateEntry { epoch, hash })?;
+                        self.track_change(path, FileChange::Updated);
                     }
                 }
             }
@@ -190,36 +738,257 @@ impl FileSearchWriteTransaction {
                 let (content, hash) = Self::get_file_data(path)?;
                 self.insert_into_state(path, FileStateEntry { epoch, hash })?;
                 self.insert_into_index(path, content)?;
+                self.track_change(path, FileChange::Added);
             }
         }
 
         Ok(())
     }
 
+    pub fn import<R: BufRead>(&mut self, input: R) -> Result<usize, Error> {
+        if !self.txn.open_table(self.tables.state())?.is_empty()? {
+            return Err(Error {
+                source: ErrorSource::Search,
+                message: "Documents can only be imported into an empty index.".into(),
+            });
+        }
+
+        let mut entries = Vec::new();
+        let mut paths = HashSet::new();
+
+        for (number, line) in input.lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry: FileExportEntry = serde_json::from_str(&line)?;
+
+            if xxh3_64(entry.content.as_bytes()) != entry.hash {
+                return Err(Error {
+                    source: ErrorSource::Search,
+                    message: format!("Hash mismatch for '{}' on line {}.", entry.path, number + 1),
+                });
+            }
+
+            if !paths.insert(entry.path.clone()) {
+                return Err(Error {
+                    source: ErrorSource::Search,
+                    message: format!("Duplicate path '{}' on line {}.", entry.path, number + 1),
+                });
+            }
+
+            entries.push(entry);
+        }
+
+        for entry in entries.iter() {
+            let state = FileStateEntry {
+                epoch: entry.epoch,
+                hash: entry.hash,
+            };
+
+            self.insert_into_state(&entry.path, state)?;
+            self.insert_into_index(&entry.path, entry.content.clone())?;
+            self.track_change(&entry.path, FileChange::Added);
+        }
+
+        Ok(entries.len())
+    }
+
     pub fn remove(&mut self, path: &str) -> Result<(), Error> {
         self.delete_from_index(path)?;
-        self.delete_from_state(path)
+
+        if self.delete_from_state(path)? {
+            self.track_change(path, FileChange::Removed);
+        }
+
+        Ok(())
     }
 
-    pub fn clear(mut self) -> Result<(), Error> {
-        self.clear_state()?;
+    pub fn clear(mut self) -> Result<FileChangeSummary, Error> {
+        for path in self.clear_state()? {
+            self.track_change(&path, FileChange::Removed);
+        }
+
         self.writer.delete_all_documents()?;
         self.commit()
     }
 
-    pub fn commit(mut self) -> Result<(), Error> {
+    pub fn changes(&self) -> FileChangeSummary {
+        FileChangeSummary::from_changes(&self.changes)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.opened_at.elapsed()
+    }
+
+    pub fn search(
+        &self,
+        committed: &FileSearchReadTransaction,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<FileSearchEntry>, Error> {
+        self.search_page(committed, query, 0, limit.unwrap_or(100_000))
+            .map(|page| page.entries)
+    }
+
+    pub fn search_page(
+        &self,
+        committed: &FileSearchReadTransaction,
+        query: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<FileSearchPage, Error> {
+        let window = search_window(offset, limit)?;
+        let changed: Vec<_> = self
+            .changes
+            .iter()
+            .filter(|(_, change)| {
+                matches!(
+                    change,
+                    FileChange::Added | FileChange::Updated | FileChange::Removed
+                )
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        let Some(log) = self.log.as_ref() else {
+            return committed
+                .search_page(query, offset, limit)
+                .map(|page| FileSearchPage {
+                    warning: Some(
+                        "The uncommitted changes exceed the transaction log budget, so only \
+                         committed documents were searched."
+                            .into(),
+                    ),
+                    ..page
+                });
+        };
+        let mut searchers = vec![(committed.reader.searcher(), changed)];
+
+        if !log.is_empty() {
+            let index = Index::create_in_ram(self.writer.index().schema());
+            register_tokenizers(&index)?;
+            let mut writer: IndexWriter<TantivyDocument> = index.writer_with_options(
+                IndexWriterOptions::builder()
+                    .memory_budget_per_thread(15_000_000)
+                    .num_worker_threads(1)
+                    .build(),
+            )?;
+            writer.run(Self::replay(log))?;
+            writer.commit()?;
+
+            let reader: IndexReader = index
+                .reader_builder()
+                .reload_policy(ReloadPolicy::Manual)
+                .try_into()?;
+            searchers.push((reader.searcher(), Vec::new()));
+        }
+
+        let statistics = FileSearchStatistics::new(
+            searchers
+                .iter()
+                .map(|(searcher, _)| searcher.clone())
+                .collect(),
+        );
+        let mut total = 0;
+        let mut entries = Vec::new();
+
+        for (searcher, excluded) in searchers.iter() {
+            let (found, page) = collect_documents(
+                searcher,
+                (self.field_path, self.field_content),
+                query,
+                (0, window),
+                Some(&statistics),
+                committed.options,
+                excluded,
+            )?;
+
+            total += found;
+            entries.extend(page);
+        }
+
+        entries.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        Ok(FileSearchPage {
+            total,
+            offset,
+            entries: entries.into_iter().skip(offset).take(limit).collect(),
+            cursor: None,
+            warning: None,
+        })
+    }
+
+    pub fn savepoints(&self) -> Vec<&str> {
+        self.savepoints
+            .iter()
+            .map(|savepoint| savepoint.name.as_str())
+            .collect()
+    }
+
+    pub fn savepoint(&mut self, name: &str) -> Result<(), Error> {
+        let log_len = self.log.as_ref().ok_or_else(Self::log_exceeded)?.len();
+
+        self.savepoints.retain(|savepoint| savepoint.name != name);
+        self.savepoints.push(FileSearchSavepoint {
+            name: name.to_owned(),
+            undo_len: self.undo.len(),
+            log_len,
+            log_bytes: self.log_bytes,
+            changes: self.changes.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn rollback_to(&mut self, name: &str) -> Result<(), Error> {
+        let position = self
+            .savepoints
+            .iter()
+            .position(|savepoint| savepoint.name == name)
+            .ok_or_else(|| Error {
+                source: ErrorSource::Search,
+                message: format!("Savepoint '{name}' does not exist."),
+            })?;
+
+        let log = self.log.as_mut().ok_or_else(Self::log_exceeded)?;
+
+        self.savepoints.truncate(position + 1);
+        let savepoint = &self.savepoints[position];
+        log.truncate(savepoint.log_len);
+        self.log_bytes = savepoint.log_bytes;
+        self.writer.rollback()?;
+        self.writer.run(Self::replay(log))?;
+
+        let undo: Vec<_> = self.undo.drain(savepoint.undo_len..).rev().collect();
+        let mut table = self.txn.open_table(self.tables.state())?;
+
+        for (path, entry) in undo {
+            match entry {
+                Some(entry) => table.insert(path.as_str(), entry)?,
+                None => table.remove(path.as_str())?,
+            };
+        }
+
+        self.changes = savepoint.changes.clone();
+        Ok(())
+    }
+
+    pub fn commit(mut self) -> Result<FileChangeSummary, Error> {
         let prep = self.writer.prepare_commit()?;
 
         match self.txn.commit() {
             Ok(_) => {
                 prep.commit()?;
             }
-            _ => {
+            Err(error) => {
                 prep.abort()?;
+                return Err(error.into());
             }
         }
 
-        Ok(())
+        Ok(FileChangeSummary::from_changes(&self.changes))
     }
 
     pub fn rollback(mut self) -> Result<(), Error> {
@@ -228,9 +997,29 @@ impl FileSearchWriteTransaction {
         Ok(())
     }
 
-    fn get_file_data(path: &str) -> Result<(String, u64), Error> {
-        let content = fs::read_to_string(path)?;
-        let hash = xxh3_64(content.as_bytes());
+    fn track_change(&mut self, path: &str, change: FileChange) {
+        let previous = self.changes.get(path).copied();
+        let next = match (previous, change) {
+            (Some(FileChange::Added), FileChange::Removed) => None,
+            (Some(FileChange::Added), _) => Some(FileChange::Added),
+            (Some(FileChange::Removed), FileChange::Added) => Some(FileChange::Updated),
+            (Some(FileChange::Updated), FileChange::Touched) => Some(FileChange::Updated),
+            (_, change) => Some(change),
+        };
+
+        match next {
+            Some(change) => {
+                self.changes.insert(path.to_owned(), change);
+            }
+            None => {
+                self.changes.remove(path);
+            }
+        }
+    }
+
+    fn get_file_data(path: &str) -> Result<(String, u64), Error> {
+        let content = fs::read_to_string(path)?;
+        let hash = xxh3_64(content.as_bytes());
         Ok((content, hash))
     }
 
@@ -243,13 +1032,14 @@ impl FileSearchWriteTransaction {
     }
 
     fn get_from_state(&self, path: &str) -> Result<Option<FileStateEntry>, Error> {
-        let table = self.txn.open_table(STATE_TABLE)?;
+        let table = self.txn.open_table(self.tables.state())?;
         Ok(table.get(path)?.map(|entry| entry.value()))
     }
 
     fn insert_into_state(&mut self, path: &str, entry: FileStateEntry) -> Result<(), Error> {
-        let mut table = self.txn.open_table(STATE_TABLE)?;
-        table.insert(path, entry)?;
+        let mut table = self.txn.open_table(self.tables.state())?;
+        let previous = table.insert(path, entry)?.map(|entry| entry.value());
+        self.undo.push((path.to_owned(), previous));
         Ok(())
     }
 
@@ -257,24 +1047,66 @@ impl FileSearchWriteTransaction {
         let mut document = TantivyDocument::new();
         document.add_field_value(self.field_path, path);
         document.add_field_value(self.field_content, &content);
-        self.writer.add_document(document)?;
+
+        if let Ok(field_trigrams) = self.writer.index().schema().get_field(TRIGRAMS_FIELD) {
+            document.add_field_value(field_trigrams, &content);
+        }
+
+        self.writer.add_document(document.clone())?;
+        self.record(UserOperation::Add(document), path.len() + content.len());
         Ok(())
     }
 
-    fn delete_from_state(&mut self, path: &str) -> Result<(), Error> {
-        let mut table = self.txn.open_table(STATE_TABLE)?;
-        table.remove(path)?;
-        Ok(())
+    fn delete_from_state(&mut self, path: &str) -> Result<bool, Error> {
+        let mut table = self.txn.open_table(self.tables.state())?;
+        let previous = table.remove(path)?.map(|entry| entry.value());
+        let removed = previous.is_some();
+
+        if removed {
+            self.undo.push((path.to_owned(), previous));
+        }
+
+        Ok(removed)
     }
 
     fn delete_from_index(&mut self, path: &str) -> Result<(), Error> {
         let term = Term::from_field_text(self.field_path, path);
-        self.writer.delete_term(term);
+        self.writer.delete_term(term.clone());
+        self.record(UserOperation::Delete(term), path.len());
         Ok(())
     }
 
-    fn clear_state(&mut self) -> Result<(), Error> {
-        let mut table = self.txn.open_table(STATE_TABLE)?;
+    fn record(&mut self, operation: UserOperation, bytes: usize) {
+        self.log_bytes += bytes;
+
+        if self.log_bytes > self.log_budget {
+            self.log = None;
+        } else if let Some(log) = self.log.as_mut() {
+            log.push(operation);
+        }
+    }
+
+    fn replay(log: &[UserOperation]) -> Vec<UserOperation> {
+        log.iter()
+            .map(|operation| match operation {
+                UserOperation::Add(document) => UserOperation::Add(document.clone()),
+                UserOperation::Delete(term) => UserOperation::Delete(term.clone()),
+            })
+            .collect()
+    }
+
+    fn log_exceeded() -> Error {
+        Error {
+            source: ErrorSource::Search,
+            message: "The uncommitted changes exceed the transaction log budget, so they can \
+                      no longer be rolled back to a savepoint. Commit or roll back the \
+                      transaction."
+                .into(),
+        }
+    }
+
+    fn clear_state(&mut self) -> Result<Vec<String>, Error> {
+        let mut table = self.txn.open_table(self.tables.state())?;
         let keys: Vec<_> = table
             .iter()?
             .map(|entry| entry.map(|(key, _)| key.value().to_owned()))
@@ -284,7 +1116,79 @@ impl FileSearchWriteTransaction {
             table.remove(key.as_str())?;
         }
 
-        Ok(())
+        Ok(keys)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FileSearchBuilder {
+    path: PathBuf,
+    collection: String,
+    memory_budget_per_thread: usize,
+    num_worker_threads: usize,
+    transaction_log_budget: usize,
+    trigrams: bool,
+}
+
+impl FileSearchBuilder {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            collection: DEFAULT_COLLECTION.into(),
+            memory_budget_per_thread: 50_000_000,
+            num_worker_threads: 1,
+            transaction_log_budget: 50_000_000,
+            trigrams: false,
+        }
+    }
+
+    pub fn collection(mut self, name: &str) -> Self {
+        self.collection = name.into();
+        self
+    }
+
+    pub fn memory_budget_per_thread(mut self, bytes: usize) -> Self {
+        self.memory_budget_per_thread = bytes;
+        self
+    }
+
+    pub fn num_worker_threads(mut self, count: usize) -> Self {
+        self.num_worker_threads = count;
+        self
+    }
+
+    pub fn transaction_log_budget(mut self, bytes: usize) -> Self {
+        self.transaction_log_budget = bytes;
+        self
+    }
+
+    pub fn trigrams(mut self, enabled: bool) -> Self {
+        self.trigrams = enabled;
+        self
+    }
+
+    pub fn open(self) -> Result<FileSearch, Error> {
+        let db = Database::create(self.path.join(DB_FILENAME))?;
+        let (schema, field_path, field_content) = FileSearch::build_schema(self.trigrams);
+        let index = open_or_create_index(MmapDirectory::open(&self.path)?, schema)?;
+        let mut searcher = FileSearch {
+            db,
+            index,
+            root: self.path.clone(),
+            path: self.path,
+            collection: DEFAULT_COLLECTION.into(),
+            tables: FileSearchTables::new(DEFAULT_COLLECTION),
+            field_path,
+            field_content,
+            memory_budget_per_thread: self.memory_budget_per_thread,
+            num_worker_threads: self.num_worker_threads,
+            transaction_log_budget: self.transaction_log_budget,
+            trigrams: self.trigrams,
+            pinned: Arc::default(),
+        };
+
+        searcher.use_collection(&self.collection)?;
+        Ok(searcher)
     }
 }
 
@@ -292,28 +1196,160 @@ impl FileSearchWriteTransaction {
 pub struct FileSearch {
     db: Database,
     index: Index,
+    root: PathBuf,
+    path: PathBuf,
+    collection: String,
+    tables: FileSearchTables,
     field_path: Field,
     field_content: Field,
+    memory_budget_per_thread: usize,
+    num_worker_threads: usize,
+    transaction_log_budget: usize,
+    trigrams: bool,
+    pinned: Arc<Mutex<VecDeque<Searcher>>>,
 }
 
 impl FileSearch {
+    pub fn builder<P: AsRef<Path>>(path: P) -> FileSearchBuilder {
+        FileSearchBuilder::new(path)
+    }
+
     pub fn create(path: &Path) -> Result<Self, Error> {
-        let db = Database::create(path.join(DB_FILENAME))?;
-        let mut schema_builder = Schema::builder();
-        let field_path = schema_builder.add_text_field("path", schema::STRING | schema::STORED);
-        let field_content = schema_builder.add_text_field("content", schema::TEXT | schema::STORED);
-        let schema = schema_builder.build();
-        let dir = MmapDirectory::open(path)?;
-        let index = Index::open_or_create(dir, schema)?;
+        Self::builder(path).open()
+    }
 
-        Ok(Self {
-            db,
-            index,
-            field_path,
-            field_content,
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn collection(&self) -> &str {
+        &self.collection
+    }
+
+    pub fn collections(&self) -> Result<Vec<FileCollectionEntry>, Error> {
+        let txn = self.db.begin_read()?;
+        let mut names = vec![DEFAULT_COLLECTION.to_owned()];
+
+        match txn.open_table(COLLECTION_TABLE) {
+            Ok(table) => {
+                for entry in table.iter()? {
+                    names.push(entry?.0.value().to_owned());
+                }
+            }
+            Err(TableError::TableDoesNotExist(_)) => {}
+            Err(error) => return Err(error.into()),
+        }
+
+        let mut result = Vec::new();
+
+        for name in names {
+            let documents = match txn.open_table(FileSearchTables::new(&name).state()) {
+                Ok(table) => table.len()?,
+                Err(TableError::TableDoesNotExist(_)) => 0,
+                Err(error) => return Err(error.into()),
+            };
+
+            result.push(FileCollectionEntry {
+                current: name == self.collection,
+                name,
+                documents,
+            });
+        }
+
+        Ok(result)
+    }
+
+    pub fn use_collection(&mut self, name: &str) -> Result<(), Error> {
+        Self::validate_name("collection", name)?;
+
+        if name == self.collection {
+            return Ok(());
+        }
+
+        let path = self.collection_path(name);
+        fs::create_dir_all(&path)?;
+
+        let (schema, _, _) = Self::build_schema(self.trigrams);
+        let index = open_or_create_index(MmapDirectory::open(&path)?, schema)?;
+
+        if name != DEFAULT_COLLECTION {
+            let created = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let txn = self.db.begin_write()?;
+
+            {
+                let mut table = txn.open_table(COLLECTION_TABLE)?;
+
+                if table.get(name)?.is_none() {
+                    table.insert(name, created)?;
+                }
+            }
+
+            txn.commit()?;
+        }
+
+        self.index = index;
+        self.path = path;
+        self.collection = name.into();
+        self.tables = FileSearchTables::new(name);
+        Ok(())
+    }
+
+    pub fn search_collections(
+        &self,
+        collections: &[&str],
+        query: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<FileSearchPage, Error> {
+        let members = collections
+            .iter()
+            .map(|collection| {
+                self.open_read_in(collection)
+                    .map(|reader| (*collection, reader.searcher()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        search_merged(&members, query, offset, limit, |entry, collection| {
+            entry.collection = Some(collection.to_string());
         })
     }
 
+    pub fn open_read_in(&self, collection: &str) -> Result<FileSearchReadTransaction, Error> {
+        if collection == self.collection {
+            return self.open_read();
+        }
+
+        let exists = self
+            .collections()?
+            .iter()
+            .any(|entry| entry.name == collection);
+
+        if !exists {
+            return Err(Error {
+                source: ErrorSource::Search,
+                message: format!("Collection '{collection}' does not exist."),
+            });
+        }
+
+        let index = open_index(MmapDirectory::open(self.collection_path(collection))?)?;
+
+        Ok(FileSearchReadTransaction::new(
+            self.db.begin_read()?,
+            index
+                .reader_builder()
+                .reload_policy(ReloadPolicy::Manual)
+                .try_into()?,
+            FileSearchTables::new(collection),
+            self.field_path,
+            self.field_content,
+            None,
+            self.pinned.clone(),
+        ))
+    }
+
     pub fn compact(&mut self) -> Result<bool, Error> {
         self.db.compact().map_err(|error| error.into())
     }
@@ -326,12 +1362,14 @@ impl FileSearch {
             writer,
             self.index.writer_with_options(
                 IndexWriterOptions::builder()
-                    .memory_budget_per_thread(50_000_000)
-                    .num_worker_threads(1)
+                    .memory_budget_per_thread(self.memory_budget_per_thread)
+                    .num_worker_threads(self.num_worker_threads)
                     .build(),
             )?,
+            self.tables.clone(),
             self.field_path,
             self.field_content,
+            self.transaction_log_budget,
         ))
     }
 
@@ -342,51 +1380,1731 @@ impl FileSearch {
                 .reader_builder()
                 .reload_policy(ReloadPolicy::OnCommitWithDelay)
                 .try_into()?,
+            self.tables.clone(),
             self.field_path,
             self.field_content,
+            None,
+            self.pinned.clone(),
         ))
     }
-}
 
-#[derive(Debug)]
-struct Bincode<T>(pub T);
+    pub fn open_read_at(&self, snapshot: &str) -> Result<FileSearchReadTransaction, Error> {
+        let txn = self.db.begin_read()?;
+        let exists = match txn.open_table(self.tables.snapshot()) {
+            Ok(table) => table.get(snapshot)?.is_some(),
+            Err(TableError::TableDoesNotExist(_)) => false,
+            Err(error) => return Err(error.into()),
+        };
 
-impl<T> RedbValue for Bincode<T>
-where
-    T: Debug + Encode + Decode<()>,
-{
-    type SelfType<'a>
-        = T
-    where
-        Self: 'a;
+        if !exists {
+            return Err(Self::snapshot_not_found(snapshot));
+        }
 
-    type AsBytes<'a>
-        = Vec<u8>
-    where
-        Self: 'a;
+        let dir = MmapDirectory::open(self.snapshot_path(snapshot))?;
+        let index = open_index(dir)?;
 
-    fn fixed_width() -> Option<usize> {
-        None
+        Ok(FileSearchReadTransaction::new(
+            txn,
+            index
+                .reader_builder()
+                .reload_policy(ReloadPolicy::Manual)
+                .try_into()?,
+            self.tables.clone(),
+            self.field_path,
+            self.field_content,
+            Some(snapshot.into()),
+            self.pinned.clone(),
+        ))
     }
 
-    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
-    where
-        Self: 'a,
-    {
-        decode_from_slice(data, bincode::config::standard())
-            .unwrap()
-            .0
+    pub fn create_snapshot(&self, name: &str) -> Result<FileSnapshotEntry, Error> {
+        Self::validate_name("snapshot", name)?;
+
+        let txn = self.db.begin_write()?;
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let documents = {
+            let mut snapshots = txn.open_table(self.tables.snapshot())?;
+
+            if snapshots.get(name)?.is_some() {
+                return Err(Error {
+                    source: ErrorSource::Search,
+                    message: format!("Snapshot '{name}' already exists."),
+                });
+            }
+
+            let states = txn.open_table(self.tables.state())?;
+            let mut snapshot_states = txn.open_table(self.tables.snapshot_state())?;
+
+            for entry in states.iter()? {
+                let (key_guard, value_guard) = entry?;
+                snapshot_states.insert((name, key_guard.value()), value_guard.value())?;
+            }
+
+            let documents = states.len()?;
+            snapshots.insert(name, FileSnapshotState { created, documents })?;
+            documents
+        };
+
+        let path = self.snapshot_path(name);
+
+        let copied = self
+            .index
+            .load_metas()
+            .map_err(Error::from)
+            .and_then(|metas| self.copy_index_files(&metas, &path, true));
+
+        if let Err(error) = copied {
+            let _ = fs::remove_dir_all(&path);
+            txn.abort()?;
+            return Err(error);
+        }
+
+        txn.commit()?;
+
+        Ok(FileSnapshotEntry {
+            name: name.into(),
+            created,
+            documents,
+        })
     }
 
-    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
-    where
-        Self: 'a,
-        Self: 'b,
-    {
-        encode_to_vec(value, bincode::config::standard()).unwrap()
+    pub fn backup(&self, target: &Path) -> Result<FileBackupManifest, Error> {
+        if target.exists() && fs::read_dir(target)?.next().is_some() {
+            return Err(Error {
+                source: ErrorSource::Search,
+                message: format!("Backup target '{}' is not empty.", target.display()),
+            });
+        }
+
+        fs::create_dir_all(target)?;
+
+        let (txn, _searcher, metas, documents) = self.consistent_state()?;
+        let backup_db = Database::create(target.join(DB_FILENAME))?;
+        let backup_txn = backup_db.begin_write()?;
+        let backup_tables = FileSearchTables::new(DEFAULT_COLLECTION);
+
+        copy_table(
+            &txn,
+            self.tables.state(),
+            &backup_txn,
+            backup_tables.state(),
+        )?;
+        copy_table(
+            &txn,
+            self.tables.snapshot(),
+            &backup_txn,
+            backup_tables.snapshot(),
+        )?;
+        copy_table(
+            &txn,
+            self.tables.snapshot_state(),
+            &backup_txn,
+            backup_tables.snapshot_state(),
+        )?;
+        backup_txn.commit()?;
+        drop(backup_db);
+
+        let mut files = self.copy_index_files(&metas, target, false)?;
+        files.push(PathBuf::from(DB_FILENAME));
+
+        let snapshots = match txn.open_table(self.tables.snapshot()) {
+            Ok(table) => table
+                .iter()?
+                .map(|entry| entry.map(|(key, _)| key.value().to_owned()))
+                .collect::<Result<Vec<_>, _>>()?,
+            Err(TableError::TableDoesNotExist(_)) => Vec::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        for snapshot in snapshots {
+            for file in fs::read_dir(self.snapshot_path(&snapshot))? {
+                let file = file?.path();
+                let relative = file.strip_prefix(&self.path).unwrap_or(&file).to_path_buf();
+
+                fs::create_dir_all(target.join(&relative).parent().unwrap_or(target))?;
+                transfer_file(&file, &target.join(&relative), false)?;
+                files.push(relative);
+            }
+        }
+
+        let mut manifest = FileBackupManifest {
+            collection: self.collection.clone(),
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis(),
+            documents,
+            files: Vec::new(),
+        };
+
+        for file in files {
+            let (size, hash) = hash_file(&target.join(&file))?;
+
+            manifest.files.push(FileBackupEntry {
+                path: file.to_string_lossy().into_owned(),
+                size,
+                hash,
+            });
+        }
+
+        fs::write(
+            target.join(BACKUP_MANIFEST_FILENAME),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+
+        Ok(manifest)
     }
 
-    fn type_name() -> TypeName {
-        TypeName::new(&format!("Bincode<{}>", type_name::<T>()))
+    pub fn verify_backup(source: &Path) -> Result<FileBackupManifest, Error> {
+        let manifest: FileBackupManifest =
+            serde_json::from_slice(&fs::read(source.join(BACKUP_MANIFEST_FILENAME))?)?;
+
+        for file in manifest.files.iter() {
+            let (size, hash) = hash_file(&source.join(&file.path))?;
+
+            if size != file.size || hash != file.hash {
+                return Err(Self::invalid_backup(&format!(
+                    "File '{}' is corrupted.",
+                    file.path
+                )));
+            }
+        }
+
+        let db = Database::open(source.join(DB_FILENAME))?;
+        let txn = db.begin_read()?;
+        let tables = FileSearchTables::new(DEFAULT_COLLECTION);
+        let documents = match txn.open_table(tables.state()) {
+            Ok(table) => table.len()?,
+            Err(TableError::TableDoesNotExist(_)) => 0,
+            Err(error) => return Err(error.into()),
+        };
+        let index = open_index(MmapDirectory::open(source)?)?;
+        let indexed = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map(|reader: IndexReader| reader.searcher().num_docs())?;
+
+        if documents != manifest.documents || indexed != manifest.documents {
+            return Err(Self::invalid_backup(
+                "Document count does not match the manifest.",
+            ));
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn restore(&mut self, source: &Path) -> Result<FileBackupManifest, Error> {
+        let manifest = Self::verify_backup(source)?;
+
+        if manifest.collection != self.collection {
+            return Err(Error {
+                source: ErrorSource::Search,
+                message: format!(
+                    "The backup holds collection '{}', not '{}'. Switch to collection '{}' before restoring it.",
+                    manifest.collection, self.collection, manifest.collection
+                ),
+            });
+        }
+
+        let staging = self.path.join(RESTORE_DIRNAME);
+        let directory = self.index.directory();
+
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+
+        for file in manifest.files.iter() {
+            let path = Path::new(&file.path);
+            let is_segment_file = file.path != DB_FILENAME
+                && file.path != INDEX_META_FILENAME
+                && !path.starts_with(SNAPSHOTS_DIRNAME);
+            let destination = staging.join(path);
+
+            fs::create_dir_all(destination.parent().unwrap_or(&staging))?;
+            transfer_file(&source.join(path), &destination, false)?;
+
+            if is_segment_file {
+                fs::File::open(&destination)?.sync_all()?;
+                directory.atomic_write(path, &[])?;
+                fs::rename(&destination, self.path.join(path))?;
+            }
+        }
+
+        let backup_db = Database::open(staging.join(DB_FILENAME))?;
+        let backup_txn = backup_db.begin_read()?;
+        let backup_tables = FileSearchTables::new(DEFAULT_COLLECTION);
+        let txn = self.db.begin_write()?;
+
+        txn.delete_table(self.tables.state())?;
+        txn.delete_table(self.tables.snapshot())?;
+        txn.delete_table(self.tables.snapshot_state())?;
+        copy_table(
+            &backup_txn,
+            backup_tables.state(),
+            &txn,
+            self.tables.state(),
+        )?;
+        copy_table(
+            &backup_txn,
+            backup_tables.snapshot(),
+            &txn,
+            self.tables.snapshot(),
+        )?;
+        copy_table(
+            &backup_txn,
+            backup_tables.snapshot_state(),
+            &txn,
+            self.tables.snapshot_state(),
+        )?;
+        txn.commit()?;
+        drop(backup_txn);
+        drop(backup_db);
+
+        let snapshots = self.path.join(SNAPSHOTS_DIRNAME);
+
+        if snapshots.exists() {
+            fs::rename(&snapshots, staging.join(RESTORE_OLD_SNAPSHOTS_DIRNAME))?;
+        }
+
+        if staging.join(SNAPSHOTS_DIRNAME).exists() {
+            fs::rename(staging.join(SNAPSHOTS_DIRNAME), &snapshots)?;
+        }
+
+        directory.atomic_write(
+            Path::new(INDEX_META_FILENAME),
+            &fs::read(staging.join(INDEX_META_FILENAME))?,
+        )?;
+        fs::remove_dir_all(&staging)?;
+
+        self.index = open_index(MmapDirectory::open(&self.path)?)?;
+        Ok(manifest)
+    }
+
+    pub fn delete_snapshot(&self, name: &str) -> Result<(), Error> {
+        let txn = self.db.begin_write()?;
+
+        {
+            let mut snapshots = txn.open_table(self.tables.snapshot())?;
+
+            if snapshots.remove(name)?.is_none() {
+                return Err(Self::snapshot_not_found(name));
+            }
+
+            let mut snapshot_states = txn.open_table(self.tables.snapshot_state())?;
+            let mut keys = Vec::new();
+
+            for entry in snapshot_states.range((name, "")..)? {
+                let (key_guard, _) = entry?;
+                let (snapshot, path) = key_guard.value();
+
+                if snapshot != name {
+                    break;
+                }
+
+                keys.push(path.to_owned());
+            }
+
+            for key in keys.iter() {
+                snapshot_states.remove((name, key.as_str()))?;
+            }
+        }
+
+        txn.commit()?;
+
+        let path = self.snapshot_path(name);
+
+        if path.exists() {
+            fs::remove_dir_all(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn consistent_state(&self) -> Result<(ReadTransaction, Searcher, IndexMeta, u64), Error> {
+        for _ in 0..CONSISTENT_STATE_ATTEMPTS {
+            let txn = self.db.begin_read()?;
+            let reader: IndexReader = self
+                .index
+                .reader_builder()
+                .reload_policy(ReloadPolicy::Manual)
+                .try_into()?;
+            let searcher = reader.searcher();
+            let metas = self.index.load_metas()?;
+            let documents = match txn.open_table(self.tables.state()) {
+                Ok(table) => table.len()?,
+                Err(TableError::TableDoesNotExist(_)) => 0,
+                Err(error) => return Err(error.into()),
+            };
+            let segments: BTreeMap<_, _> = metas
+                .segments
+                .iter()
+                .map(|segment| (segment.id(), segment.delete_opstamp()))
+                .collect();
+
+            if &segments == searcher.generation().segments() && documents == searcher.num_docs() {
+                return Ok((txn, searcher, metas, documents));
+            }
+        }
+
+        Err(Error {
+            source: ErrorSource::Search,
+            message: "The index kept changing while it was being copied. Please try again.".into(),
+        })
+    }
+
+    fn copy_index_files(
+        &self,
+        metas: &IndexMeta,
+        target: &Path,
+        link: bool,
+    ) -> Result<Vec<PathBuf>, Error> {
+        let mut files = Vec::new();
+        fs::create_dir_all(target)?;
+
+        for segment in metas.segments.iter() {
+            let deletes = segment.relative_path(SegmentComponent::Delete);
+
+            for file in segment.list_files() {
+                let source = self.path.join(&file);
+
+                if file == deletes && !segment.has_deletes() {
+                    continue;
+                }
+
+                if !source.exists() {
+                    return Err(Error {
+                        source: ErrorSource::Search,
+                        message: format!("Index file '{}' is missing.", file.display()),
+                    });
+                }
+
+                transfer_file(&source, &target.join(&file), link)?;
+                files.push(file);
+            }
+        }
+
+        fs::write(
+            target.join(INDEX_META_FILENAME),
+            serde_json::to_vec_pretty(metas)?,
+        )?;
+        files.push(PathBuf::from(INDEX_META_FILENAME));
+        Ok(files)
+    }
+
+    fn build_schema(trigrams: bool) -> (Schema, Field, Field) {
+        let mut schema_builder = Schema::builder();
+        let field_path = schema_builder.add_text_field(PATH_FIELD, schema::STRING | schema::STORED);
+        let field_content =
+            schema_builder.add_text_field(CONTENT_FIELD, schema::TEXT | schema::STORED);
+
+        if trigrams {
+            let indexing = TextFieldIndexing::default()
+                .set_tokenizer(TRIGRAM_TOKENIZER)
+                .set_index_option(IndexRecordOption::Basic);
+            let options = TextOptions::default().set_indexing_options(indexing);
+            schema_builder.add_text_field(TRIGRAMS_FIELD, options);
+        }
+
+        (schema_builder.build(), field_path, field_content)
+    }
+
+    fn collection_path(&self, name: &str) -> PathBuf {
+        match name {
+            DEFAULT_COLLECTION => self.root.clone(),
+            _ => self.root.join(COLLECTIONS_DIRNAME).join(name),
+        }
+    }
+
+    fn snapshot_path(&self, name: &str) -> PathBuf {
+        self.path.join(SNAPSHOTS_DIRNAME).join(name)
+    }
+
+    fn validate_name(kind: &str, name: &str) -> Result<(), Error> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+        if valid {
+            Ok(())
+        } else {
+            Err(Error {
+                source: ErrorSource::Search,
+                message: format!("Invalid {kind} name '{name}'."),
+            })
+        }
+    }
+
+    fn invalid_backup(message: &str) -> Error {
+        Error {
+            source: ErrorSource::Search,
+            message: format!("Invalid backup. {message}"),
+        }
+    }
+
+    fn snapshot_not_found(name: &str) -> Error {
+        Error {
+            source: ErrorSource::Search,
+            message: format!("Snapshot '{name}' does not exist."),
+        }
+    }
+}
+
+pub(crate) fn search_merged<L, F>(
+    members: &[(L, Searcher)],
+    query: &str,
+    offset: usize,
+    limit: usize,
+    label: F,
+) -> Result<FileSearchPage, Error>
+where
+    F: Fn(&mut FileSearchEntry, &L),
+{
+    let window = search_window(offset, limit)?;
+    let statistics = FileSearchStatistics::new(
+        members
+            .iter()
+            .map(|(_, searcher)| searcher.clone())
+            .collect(),
+    );
+    let mut total = 0;
+    let mut entries = Vec::new();
+
+    for (name, searcher) in members.iter() {
+        let schema = searcher.schema();
+        let (found, page) = collect_documents(
+            searcher,
+            (
+                schema.get_field(PATH_FIELD)?,
+                schema.get_field(CONTENT_FIELD)?,
+            ),
+            query,
+            (0, window),
+            Some(&statistics),
+            FileQueryOptions::default(),
+            &[],
+        )?;
+
+        total += found;
+
+        for mut entry in page {
+            label(&mut entry, name);
+            entries.push(entry);
+        }
+    }
+
+    entries.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    Ok(FileSearchPage {
+        total,
+        offset,
+        entries: entries.into_iter().skip(offset).take(limit).collect(),
+        cursor: None,
+        warning: None,
+    })
+}
+
+fn search_documents(
+    searcher: &Searcher,
+    field_path: Field,
+    field_content: Field,
+    query: &str,
+    limit: Option<usize>,
+    statistics: Option<&dyn Bm25StatisticsProvider>,
+    options: FileQueryOptions,
+) -> Result<Vec<FileSearchEntry>, Error> {
+    let (_, entries) = collect_documents(
+        searcher,
+        (field_path, field_content),
+        query,
+        (0, limit.unwrap_or(100_000)),
+        statistics,
+        options,
+        &[],
+    )?;
+
+    Ok(entries)
+}
+
+fn collect_documents(
+    searcher: &Searcher,
+    (field_path, field_content): (Field, Field),
+    query: &str,
+    (offset, limit): (usize, usize),
+    statistics: Option<&dyn Bm25StatisticsProvider>,
+    options: FileQueryOptions,
+    excluded: &[String],
+) -> Result<(usize, Vec<FileSearchEntry>), Error> {
+    let index = searcher.index();
+    let mut tokenizer = index.tokenizer_for_field(field_content)?;
+    let (parsed, matcher): (Box<dyn Query>, _) = match options.mode {
+        FileSearchMode::Query => {
+            let schema = index.schema();
+            let name = schema.get_field_name(field_content);
+            let query_parser = QueryParser::for_index(index, vec![field_content]);
+            let ast = query::parse(query)?;
+            let matcher = QueryMatcher::new(ast.clone(), name, &mut tokenizer, options.fuzzy)?;
+            let parsed = query::build(
+                &query_parser,
+                ast,
+                (field_content, name),
+                &mut tokenizer,
+                options.fuzzy,
+            )?;
+
+            (parsed, matcher)
+        }
+        FileSearchMode::Regex => {
+            let matcher = QueryMatcher::Regex(query::regex(query, true)?);
+
+            (
+                Box::new(RegexQuery::from_pattern(query, field_content)?),
+                matcher,
+            )
+        }
+        FileSearchMode::Substring => {
+            let candidates = match searcher.schema().get_field(TRIGRAMS_FIELD) {
+                Ok(field_trigrams) => query::trigrams(query, field_trigrams),
+                _ => Box::new(AllQuery),
+            };
+
+            return verify_documents(
+                searcher,
+                (field_path, field_content),
+                exclude_paths(candidates, field_path, excluded).as_ref(),
+                (offset, limit),
+                options.snippets,
+                |content| {
+                    content
+                        .match_indices(query)
+                        .map(|(start, found)| start..start + found.len())
+                        .collect()
+                },
+            );
+        }
+        FileSearchMode::RegexContent => {
+            let regex = query::regex(query, false)?;
+
+            return verify_documents(
+                searcher,
+                (field_path, field_content),
+                exclude_paths(Box::new(AllQuery), field_path, excluded).as_ref(),
+                (offset, limit),
+                options.snippets,
+                |content| {
+                    regex
+                        .find_iter(content)
+                        .map(|found| found.range())
+                        .collect()
+                },
+            );
+        }
+    };
+    let parsed = exclude_paths(parsed, field_path, excluded);
+    let documents = searcher.num_docs() as usize;
+    let collector = (
+        Count,
+        TopDocs::with_limit(limit.min(documents).max(1)).and_offset(offset.min(documents)),
+    );
+    let mut weights = HashMap::new();
+    let (total, top_docs) = match statistics {
+        Some(statistics) => {
+            searcher.search_with_statistics_provider(&parsed, &collector, statistics)?
+        }
+        _ => searcher.search(&parsed, &collector)?,
+    };
+    let mut entries = Vec::new();
+
+    for (score, doc_address) in top_docs {
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        let mut fragments: HashMap<String, Vec<Range<usize>>> = HashMap::new();
+        let mut candidates = Vec::new();
+        let mut matches = Vec::new();
+        let content = get_doc_value(&doc, field_content);
+
+        if let Some(content) = content {
+            let mut tokens = Vec::new();
+
+            tokenizer.token_stream(content).process(&mut |token| {
+                tokens.push(HighlightToken {
+                    position: token.position,
+                    range: token.offset_from..token.offset_to,
+                    text: token.text.to_lowercase(),
+                })
+            });
+
+            let marked = matcher.highlight(&tokens);
+            let mut candidate = FileSnippetCandidate::new(0);
+            let mut lines = FileLineCursor::new(content);
+
+            for (token, marked) in tokens.into_iter().zip(marked) {
+                if marked {
+                    if candidate.score == 0.0 {
+                        candidate = FileSnippetCandidate::new(token.range.start);
+                    } else if token.range.end - candidate.start > options.snippets.max_length {
+                        candidates.push(candidate);
+                        candidate = FileSnippetCandidate::new(token.range.start);
+                    }
+
+                    candidate.stop = token.range.end;
+
+                    let weight = match weights.get(&token.text) {
+                        Some(weight) => *weight,
+                        _ => {
+                            let term = Term::from_field_text(field_content, &token.text);
+                            let doc_freq = match statistics {
+                                Some(statistics) => statistics.doc_freq(&term)?,
+                                _ => searcher.doc_freq(&term)?,
+                            };
+                            let weight = 1.0 / (1.0 + doc_freq as f32);
+
+                            weights.insert(token.text.clone(), weight);
+                            weight
+                        }
+                    };
+
+                    candidate.score += weight;
+                    candidate.highlights.push(token.range.clone());
+                    matches.push(lines.locate(&token.text, token.range.clone()));
+                    fragments.entry(token.text).or_default().push(token.range);
+                }
+            }
+
+            if candidate.score > 0.0 {
+                candidates.push(candidate);
+            }
+
+            for candidate in candidates.iter_mut() {
+                center_snippet(content, candidate, options.snippets.max_length);
+            }
+        }
+
+        if let Some(path) = get_doc_value(&doc, field_path) {
+            entries.push(FileSearchEntry {
+                index: None,
+                collection: None,
+                score,
+                fragments,
+                matches,
+                snippets: select_snippets(
+                    content.unwrap_or_default(),
+                    candidates,
+                    options.snippets.count,
+                ),
+                path: path.into(),
+            });
+        }
+    }
+
+    Ok((total, entries))
+}
+
+fn search_window(offset: usize, limit: usize) -> Result<usize, Error> {
+    if limit == 0 {
+        return Err(Error {
+            source: ErrorSource::Search,
+            message: "The search limit must be greater than zero.".into(),
+        });
+    }
+
+    offset.checked_add(limit).ok_or_else(|| Error {
+        source: ErrorSource::Search,
+        message: format!("The search offset {offset} is out of range."),
+    })
+}
+
+fn exclude_paths(query: Box<dyn Query>, field_path: Field, paths: &[String]) -> Box<dyn Query> {
+    if paths.is_empty() {
+        return query;
+    }
+
+    let mut clauses = vec![(Occur::Must, query)];
+
+    for path in paths {
+        let term = Term::from_field_text(field_path, path);
+        clauses.push((
+            Occur::MustNot,
+            Box::new(TermQuery::new(term, IndexRecordOption::Basic)),
+        ));
+    }
+
+    Box::new(BooleanQuery::new(clauses))
+}
+
+fn verify_documents<F>(
+    searcher: &Searcher,
+    (field_path, field_content): (Field, Field),
+    candidates: &dyn Query,
+    (offset, limit): (usize, usize),
+    snippets: FileSnippetOptions,
+    find: F,
+) -> Result<(usize, Vec<FileSearchEntry>), Error>
+where
+    F: Fn(&str) -> Vec<Range<usize>>,
+{
+    let mut found = Vec::new();
+
+    for doc_address in searcher.search(candidates, &DocSetCollector)? {
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        let (Some(path), Some(content)) = (
+            get_doc_value(&doc, field_path),
+            get_doc_value(&doc, field_content),
+        ) else {
+            continue;
+        };
+        let ranges: Vec<_> = find(content)
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect();
+
+        if !ranges.is_empty() {
+            found.push((path.to_string(), doc_address, ranges));
+        }
+    }
+
+    found.sort_by(|a, b| b.2.len().cmp(&a.2.len()).then_with(|| a.0.cmp(&b.0)));
+
+    let total = found.len();
+    let mut entries = Vec::new();
+
+    for (path, doc_address, ranges) in found.into_iter().skip(offset).take(limit) {
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        let content = get_doc_value(&doc, field_content).unwrap_or_default();
+        let mut fragments: HashMap<String, Vec<Range<usize>>> = HashMap::new();
+        let mut matches = Vec::new();
+        let mut candidates = Vec::new();
+        let mut candidate = FileSnippetCandidate::new(ranges[0].start);
+        let mut lines = FileLineCursor::new(content);
+
+        for range in &ranges {
+            if candidate.score > 0.0 && range.end - candidate.start > snippets.max_length {
+                candidates.push(candidate);
+                candidate = FileSnippetCandidate::new(range.start);
+            }
+
+            let text = &content[range.clone()];
+
+            candidate.stop = range.end;
+            candidate.score += 1.0;
+            candidate.highlights.push(range.clone());
+            matches.push(lines.locate(text, range.clone()));
+            fragments
+                .entry(text.into())
+                .or_default()
+                .push(range.clone());
+        }
+
+        candidates.push(candidate);
+
+        for candidate in candidates.iter_mut() {
+            let start = content[..candidate.start]
+                .rfind('\n')
+                .map_or(0, |index| index + 1);
+            let stop = content[candidate.stop..]
+                .find('\n')
+                .map_or(content.len(), |index| candidate.stop + index);
+
+            if stop - start <= snippets.max_length {
+                (candidate.start, candidate.stop) = (start, stop);
+            } else {
+                center_snippet(content, candidate, snippets.max_length);
+            }
+        }
+
+        entries.push(FileSearchEntry {
+            index: None,
+            collection: None,
+            score: ranges.len() as f32,
+            fragments,
+            matches,
+            snippets: select_snippets(content, candidates, snippets.count),
+            path,
+        });
+    }
+
+    Ok((total, entries))
+}
+
+fn center_snippet(content: &str, candidate: &mut FileSnippetCandidate, max_length: usize) {
+    let span = candidate.stop - candidate.start;
+
+    if span >= max_length {
+        return;
+    }
+
+    let padding = max_length - span;
+    let after = (padding - padding / 2).min(content.len() - candidate.stop);
+    let before = (padding - after).min(candidate.start);
+    let after = (padding - before).min(content.len() - candidate.stop);
+    let mut start = candidate.start - before;
+    let mut stop = candidate.stop + after;
+
+    while !content.is_char_boundary(start) {
+        start += 1;
+    }
+
+    while !content.is_char_boundary(stop) {
+        stop -= 1;
+    }
+
+    if start > 0 && !content[..start].ends_with(char::is_whitespace) {
+        start = content[start..candidate.start]
+            .find(char::is_whitespace)
+            .map_or(candidate.start, |index| start + index);
+    }
+
+    if stop < content.len() && !content[stop..].starts_with(char::is_whitespace) {
+        stop = content[candidate.stop..stop]
+            .rfind(char::is_whitespace)
+            .map_or(candidate.stop, |index| candidate.stop + index);
+    }
+
+    let leading =
+        content[start..candidate.start].len() - content[start..candidate.start].trim_start().len();
+    let trailing =
+        content[candidate.stop..stop].len() - content[candidate.stop..stop].trim_end().len();
+
+    (candidate.start, candidate.stop) = (start + leading, stop - trailing);
+}
+
+fn select_snippets(
+    content: &str,
+    mut candidates: Vec<FileSnippetCandidate>,
+    count: usize,
+) -> Vec<FileSnippet> {
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.start.cmp(&b.start)));
+    candidates.truncate(count);
+    candidates.sort_by_key(|candidate| candidate.start);
+
+    candidates
+        .into_iter()
+        .map(|candidate| FileSnippet {
+            text: content[candidate.start..candidate.stop].to_string(),
+            highlights: candidate
+                .highlights
+                .into_iter()
+                .map(|range| range.start - candidate.start..range.end - candidate.start)
+                .collect(),
+        })
+        .collect()
+}
+
+fn searcher_generation(searcher: &Searcher) -> u64 {
+    let segments: String = searcher
+        .generation()
+        .segments()
+        .iter()
+        .map(|(segment, opstamp)| format!("{}:{};", segment.uuid_string(), opstamp.unwrap_or(0)))
+        .collect();
+
+    xxh3_64(segments.as_bytes())
+}
+
+fn encode_cursor(cursor: &FileSearchCursor) -> Result<String, Error> {
+    let bytes = encode_to_vec(cursor, bincode::config::standard()).map_err(|error| Error {
+        source: ErrorSource::Search,
+        message: error.to_string(),
+    })?;
+
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+fn decode_cursor(cursor: &str) -> Result<FileSearchCursor, Error> {
+    let invalid = || Error {
+        source: ErrorSource::Search,
+        message: "The search cursor is invalid.".into(),
+    };
+
+    if !cursor.len().is_multiple_of(2) || !cursor.is_ascii() {
+        return Err(invalid());
+    }
+
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&cursor[index..index + 2], 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+
+    decode_from_slice(&bytes, bincode::config::standard())
+        .map(|(cursor, _)| cursor)
+        .map_err(|_| invalid())
+}
+
+fn copy_table<K, V>(
+    source: &ReadTransaction,
+    source_definition: TableDefinition<K, V>,
+    target: &WriteTransaction,
+    target_definition: TableDefinition<K, V>,
+) -> Result<(), Error>
+where
+    K: Key + 'static,
+    V: RedbValue + 'static,
+{
+    let table = match source.open_table(source_definition) {
+        Ok(table) => table,
+        Err(TableError::TableDoesNotExist(_)) => return Ok(()),
+        Err(error) => return Err(error.into()),
+    };
+    let mut target_table = target.open_table(target_definition)?;
+
+    for entry in table.iter()? {
+        let (key_guard, value_guard) = entry?;
+        target_table.insert(key_guard.value(), value_guard.value())?;
+    }
+
+    Ok(())
+}
+
+fn hash_file(path: &Path) -> Result<(u64, u64), Error> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Xxh3::new();
+    let mut buffer = vec![0; 1 << 16];
+    let mut size = 0;
+
+    loop {
+        let read = file.read(&mut buffer)?;
+
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok((size, hasher.digest()))
+}
+
+fn transfer_file(source: &Path, destination: &Path, link: bool) -> Result<(), Error> {
+    if !link || fs::hard_link(source, destination).is_err() {
+        fs::copy(source, destination)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn open_index(directory: MmapDirectory) -> Result<Index, Error> {
+    let index = Index::open(directory)?;
+    register_tokenizers(&index)?;
+    Ok(index)
+}
+
+fn open_or_create_index(directory: MmapDirectory, schema: Schema) -> Result<Index, Error> {
+    if Index::exists(&directory).map_err(TantivyError::from)? {
+        let index = open_index(directory)?;
+
+        if schema.get_field(TRIGRAMS_FIELD).is_ok()
+            && index.schema().get_field(TRIGRAMS_FIELD).is_err()
+        {
+            return Err(Error {
+                source: ErrorSource::Search,
+                message: "The index was created without trigrams. Export it and import into a new index created with trigrams.".into(),
+            });
+        }
+
+        return Ok(index);
+    }
+
+    let index = Index::create(directory, schema, IndexSettings::default())?;
+    register_tokenizers(&index)?;
+    Ok(index)
+}
+
+fn register_tokenizers(index: &Index) -> Result<(), Error> {
+    let tokenizer = NgramTokenizer::new(3, 3, false)?;
+    index
+        .tokenizers()
+        .register(TRIGRAM_TOKENIZER, TextAnalyzer::from(tokenizer));
+    Ok(())
+}
+
+fn get_doc_value(doc: &TantivyDocument, field: Field) -> Option<&str> {
+    doc.get_first(field).and_then(|value| value.as_str())
+}
+
+#[derive(Debug)]
+struct Bincode<T>(pub T);
+
+impl<T> RedbValue for Bincode<T>
+where
+    T: Debug + Encode + Decode<()>,
+{
+    type SelfType<'a>
+        = T
+    where
+        Self: 'a;
+
+    type AsBytes<'a>
+        = Vec<u8>
+    where
+        Self: 'a;
+
+    fn fixed_width() -> Option<usize> {
+        None
+    }
+
+    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
+    where
+        Self: 'a,
+    {
+        decode_from_slice(data, bincode::config::standard())
+            .unwrap()
+            .0
+    }
+
+    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
+    where
+        Self: 'a,
+        Self: 'b,
+    {
+        encode_to_vec(value, bincode::config::standard()).unwrap()
+    }
+
+    fn type_name() -> TypeName {
+        TypeName::new(&format!("Bincode<{}>", type_name::<T>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("file-search-{}-{name}", process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(path.join("index")).unwrap();
+            Self(path)
+        }
+
+        fn index(&self) -> PathBuf {
+            self.0.join("index")
+        }
+
+        fn file(&self, name: &str, content: &str) -> String {
+            let path = self.0.join(name);
+            fs::write(&path, content).unwrap();
+            path.to_string_lossy().into_owned()
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn committed_paths(searcher: &FileSearch) -> Vec<String> {
+        let mut paths: Vec<_> = searcher
+            .open_read()
+            .unwrap()
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn track_change_folds_changes_to_the_same_path() {
+        let dir = TestDir::new("track-change");
+        let searcher = FileSearch::create(&dir.index()).unwrap();
+        let kept = dir.file("kept.txt", "kept");
+        let dropped = dir.file("dropped.txt", "dropped");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&kept).unwrap();
+        writer.commit().unwrap();
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.remove(&kept).unwrap();
+        writer.add(&kept).unwrap();
+        writer.add(&dropped).unwrap();
+        writer.remove(&dropped).unwrap();
+        let summary = writer.commit().unwrap();
+
+        assert_eq!(summary.updated, [kept.as_str()]);
+        assert!(summary.added.is_empty());
+        assert!(summary.removed.is_empty());
+        assert_eq!(committed_paths(&searcher), [kept]);
+    }
+
+    #[test]
+    fn rollback_to_undoes_changes_after_the_savepoint() {
+        let dir = TestDir::new("rollback-to");
+        let searcher = FileSearch::create(&dir.index()).unwrap();
+        let first = dir.file("first.txt", "alpha");
+        let second = dir.file("second.txt", "bravo");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&first).unwrap();
+        writer.savepoint("before").unwrap();
+        writer.add(&second).unwrap();
+        writer.remove(&first).unwrap();
+        writer.rollback_to("before").unwrap();
+
+        assert_eq!(writer.changes().added, [first.as_str()]);
+        assert_eq!(writer.savepoints(), ["before"]);
+
+        let summary = writer.commit().unwrap();
+        let reader = searcher.open_read().unwrap();
+
+        assert_eq!(summary.added, [first.as_str()]);
+        assert_eq!(committed_paths(&searcher), [first]);
+        assert_eq!(reader.search("alpha", None).unwrap().len(), 1);
+        assert!(reader.search("bravo", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rollback_to_drops_later_savepoints() {
+        let dir = TestDir::new("rollback-to-nested");
+        let searcher = FileSearch::create(&dir.index()).unwrap();
+        let first = dir.file("first.txt", "alpha");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.savepoint("outer").unwrap();
+        writer.savepoint("inner").unwrap();
+        writer.add(&first).unwrap();
+        writer.rollback_to("outer").unwrap();
+
+        assert_eq!(writer.savepoints(), ["outer"]);
+        assert!(writer.rollback_to("inner").is_err());
+        assert!(writer.commit().unwrap().added.is_empty());
+    }
+
+    #[test]
+    fn rollback_to_fails_once_the_log_budget_is_exceeded() {
+        let dir = TestDir::new("rollback-to-budget");
+        let searcher = FileSearch::builder(dir.index())
+            .transaction_log_budget(1)
+            .open()
+            .unwrap();
+        let first = dir.file("first.txt", "alpha");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.savepoint("before").unwrap();
+        writer.add(&first).unwrap();
+
+        assert!(writer.rollback_to("before").is_err());
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let cursor = FileSearchCursor {
+            generation: 42,
+            query: "needle".into(),
+            fuzzy: Some(1),
+            mode: FileSearchMode::Substring,
+            offset: 20,
+            limit: 10,
+        };
+        let decoded = decode_cursor(&encode_cursor(&cursor).unwrap()).unwrap();
+
+        assert_eq!(decoded.generation, 42);
+        assert_eq!(decoded.query, "needle");
+        assert_eq!(decoded.fuzzy, Some(1));
+        assert_eq!(decoded.mode, FileSearchMode::Substring);
+        assert_eq!((decoded.offset, decoded.limit), (20, 10));
+    }
+
+    #[test]
+    fn invalid_cursors_are_rejected() {
+        let cursor = FileSearchCursor {
+            generation: 42,
+            query: "needle".into(),
+            fuzzy: None,
+            mode: FileSearchMode::Query,
+            offset: 0,
+            limit: 10,
+        };
+        let encoded = encode_cursor(&cursor).unwrap();
+
+        for invalid in ["", "0", "zz", "éé", &encoded[..encoded.len() - 2]] {
+            assert!(decode_cursor(invalid).is_err(), "accepted '{invalid}'");
+        }
+    }
+
+    #[test]
+    fn search_cursor_continues_the_page() {
+        let dir = TestDir::new("search-cursor");
+        let searcher = FileSearch::create(&dir.index()).unwrap();
+        let mut writer = searcher.open_write().unwrap();
+
+        for index in 0..3 {
+            writer
+                .add(&dir.file(&format!("{index}.txt"), "needle"))
+                .unwrap();
+        }
+
+        writer.commit().unwrap();
+
+        let reader = searcher.open_read().unwrap();
+        let first = reader.search_page("needle", 0, 2).unwrap();
+        let second = reader
+            .search_cursor(first.cursor.as_deref().unwrap())
+            .unwrap();
+
+        assert_eq!((first.total, first.entries.len()), (3, 2));
+        assert_eq!((second.offset, second.entries.len()), (2, 1));
+        assert!(second.cursor.is_none());
+        assert!(reader.search_cursor("00").is_err());
+    }
+
+    fn locate_all(content: &str, term: &str) -> Vec<FileMatch> {
+        let mut lines = FileLineCursor::new(content);
+
+        content
+            .match_indices(term)
+            .map(|(start, term)| lines.locate(term, start..start + term.len()))
+            .collect()
+    }
+
+    #[test]
+    fn locate_counts_lines_across_crlf() {
+        let matches = locate_all("first\r\nsecond needle\r\n\r\nneedle", "needle");
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[0].line, matches[0].column.bytes), (2, 7));
+        assert_eq!(matches[0].line_text, "second needle");
+        assert_eq!((matches[1].line, matches[1].column.bytes), (4, 0));
+        assert_eq!(matches[1].line_text, "needle");
+    }
+
+    #[test]
+    fn locate_reports_utf16_columns() {
+        let matches = locate_all("top\né😀 needle", "needle");
+        let column = matches[0].column;
+
+        assert_eq!(matches[0].line, 2);
+        assert_eq!((column.bytes, column.chars, column.utf16), (7, 3, 4));
+    }
+
+    #[test]
+    fn import_rejects_duplicate_paths() {
+        let dir = TestDir::new("import-duplicates");
+        let source = FileSearch::create(&dir.index()).unwrap();
+        let mut writer = source.open_write().unwrap();
+        writer.add(&dir.file("fox.txt", "the quick fox")).unwrap();
+        writer.commit().unwrap();
+
+        let mut exported = Vec::new();
+        source.open_read().unwrap().export(&mut exported).unwrap();
+        let line = String::from_utf8(exported).unwrap();
+
+        fs::create_dir_all(dir.0.join("copy")).unwrap();
+        let target = FileSearch::create(&dir.0.join("copy")).unwrap();
+        let mut writer = target.open_write().unwrap();
+        let error = writer
+            .import(format!("{line}{line}").as_bytes())
+            .unwrap_err();
+
+        assert!(
+            error.message.starts_with("Duplicate path"),
+            "{}",
+            error.message
+        );
+        writer.rollback().unwrap();
+
+        let mut writer = target.open_write().unwrap();
+        assert_eq!(writer.import(line.as_bytes()).unwrap(), 1);
+        writer.commit().unwrap();
+
+        let reader = target.open_read().unwrap();
+        assert_eq!(reader.search_page("fox", 0, 10).unwrap().total, 1);
+    }
+
+    #[test]
+    fn collections_keep_documents_apart() {
+        let dir = TestDir::new("collections");
+        let mut searcher = FileSearch::create(&dir.index()).unwrap();
+        let notes = dir.file("notes.txt", "shared alpha");
+        let code = dir.file("code.rs", "shared bravo");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&notes).unwrap();
+        writer.commit().unwrap();
+
+        searcher.use_collection("code").unwrap();
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&code).unwrap();
+        writer.commit().unwrap();
+
+        assert_eq!(committed_paths(&searcher), [code.as_str()]);
+        assert_eq!(
+            searcher
+                .open_read()
+                .unwrap()
+                .search("alpha", None)
+                .unwrap()
+                .len(),
+            0
+        );
+
+        let collections: Vec<_> = searcher
+            .collections()
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.name, entry.documents, entry.current))
+            .collect();
+
+        assert_eq!(
+            collections,
+            [("default".into(), 1, false), ("code".into(), 1, true)]
+        );
+
+        let page = searcher
+            .search_collections(&["default", "code"], "shared", 0, 10)
+            .unwrap();
+        let mut labels: Vec<_> = page
+            .entries
+            .into_iter()
+            .map(|entry| (entry.collection.unwrap(), entry.path))
+            .collect();
+        labels.sort();
+
+        assert_eq!(
+            labels,
+            [("code".into(), code), ("default".into(), notes.clone())]
+        );
+
+        searcher.use_collection("default").unwrap();
+        assert_eq!(committed_paths(&searcher), [notes]);
+    }
+
+    #[test]
+    fn restore_refuses_a_backup_of_another_collection() {
+        let dir = TestDir::new("backup-collection");
+        let mut searcher = FileSearch::create(&dir.index()).unwrap();
+        let code = dir.file("code.rs", "bravo");
+
+        searcher.use_collection("code").unwrap();
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&code).unwrap();
+        writer.commit().unwrap();
+
+        let manifest = searcher.backup(&dir.0.join("backup")).unwrap();
+        assert_eq!(
+            (manifest.collection.as_str(), manifest.documents),
+            ("code", 1)
+        );
+
+        searcher.use_collection("default").unwrap();
+        assert!(searcher.restore(&dir.0.join("backup")).is_err());
+        assert!(committed_paths(&searcher).is_empty());
+
+        searcher.use_collection("code").unwrap();
+        searcher.restore(&dir.0.join("backup")).unwrap();
+        assert_eq!(committed_paths(&searcher), [code]);
+    }
+
+    #[test]
+    fn rollback_to_returns_the_log_budget() {
+        let dir = TestDir::new("rollback-to-budget-returned");
+        let content = "alpha ".repeat(20);
+        let first = dir.file("first.txt", &content);
+        let second = dir.file("second.txt", &content);
+        let searcher = FileSearch::builder(dir.index())
+            .transaction_log_budget(first.len() + content.len())
+            .open()
+            .unwrap();
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.savepoint("before").unwrap();
+
+        for _ in 0..3 {
+            writer.add(&first).unwrap();
+            writer.rollback_to("before").unwrap();
+        }
+
+        writer.add(&first).unwrap();
+        writer.savepoint("after").unwrap();
+        writer.add(&second).unwrap();
+
+        assert!(writer.savepoint("late").is_err());
+        assert!(writer.rollback_to("after").is_err());
+    }
+
+    fn page_paths(page: &FileSearchPage) -> Vec<String> {
+        let mut paths: Vec<_> = page
+            .entries
+            .iter()
+            .map(|entry| entry.path.clone())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn transaction_search_merges_uncommitted_changes() {
+        let dir = TestDir::new("transaction-search");
+        let searcher = FileSearch::create(&dir.index()).unwrap();
+        let kept = dir.file("kept.txt", "alpha kept");
+        let updated = dir.file("updated.txt", "alpha old");
+        let removed = dir.file("removed.txt", "alpha removed");
+        let added = dir.file("added.txt", "alpha added");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&kept).unwrap();
+        writer.add(&updated).unwrap();
+        writer.add(&removed).unwrap();
+        writer.commit().unwrap();
+
+        dir.file("updated.txt", "alpha new");
+        fs::File::options()
+            .write(true)
+            .open(&updated)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.remove(&removed).unwrap();
+        writer.add(&updated).unwrap();
+        writer.add(&added).unwrap();
+
+        let committed = searcher.open_read().unwrap();
+        let page = writer.search_page(&committed, "alpha", 0, 10).unwrap();
+
+        assert_eq!(page.total, 3);
+        assert_eq!(
+            page_paths(&page),
+            [added.as_str(), kept.as_str(), updated.as_str()]
+        );
+        assert!(page.warning.is_none());
+
+        let page = writer.search_page(&committed, "new", 0, 10).unwrap();
+        assert_eq!(page_paths(&page), [updated.as_str()]);
+
+        let page = writer
+            .search_page(&committed, "old removed", 0, 10)
+            .unwrap();
+        assert_eq!(page.total, 0);
+
+        let page = writer.search_page(&committed, "alpha", 1, 1).unwrap();
+        assert_eq!((page.total, page.entries.len()), (3, 1));
+    }
+
+    #[test]
+    fn transaction_search_falls_back_once_the_log_budget_is_exceeded() {
+        let dir = TestDir::new("transaction-search-budget");
+        let searcher = FileSearch::builder(dir.index())
+            .transaction_log_budget(1)
+            .open()
+            .unwrap();
+        let kept = dir.file("kept.txt", "alpha kept");
+        let added = dir.file("added.txt", "alpha added");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&kept).unwrap();
+        writer.commit().unwrap();
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&added).unwrap();
+
+        let committed = searcher.open_read().unwrap();
+        let page = writer.search_page(&committed, "alpha", 0, 10).unwrap();
+
+        assert_eq!(page_paths(&page), [kept.as_str()]);
+        assert!(page.warning.is_some());
+    }
+
+    #[test]
+    fn restore_brings_back_the_backed_up_state() {
+        let dir = TestDir::new("backup-round-trip");
+        let mut searcher = FileSearch::create(&dir.index()).unwrap();
+        let first = dir.file("first.txt", "alpha");
+        let second = dir.file("second.txt", "bravo");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&first).unwrap();
+        writer.commit().unwrap();
+        searcher.create_snapshot("before").unwrap();
+
+        let manifest = searcher.backup(&dir.0.join("backup")).unwrap();
+        assert_eq!(manifest.documents, 1);
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.remove(&first).unwrap();
+        writer.add(&second).unwrap();
+        writer.commit().unwrap();
+        searcher.delete_snapshot("before").unwrap();
+
+        searcher.restore(&dir.0.join("backup")).unwrap();
+
+        let reader = searcher.open_read().unwrap();
+        assert_eq!(committed_paths(&searcher), [first.as_str()]);
+        assert_eq!(reader.search("alpha", None).unwrap().len(), 1);
+        assert!(reader.search("bravo", None).unwrap().is_empty());
+        assert_eq!(
+            searcher
+                .open_read_at("before")
+                .unwrap()
+                .list()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&second).unwrap();
+        writer.commit().unwrap();
+        assert_eq!(committed_paths(&searcher).len(), 2);
+    }
+
+    #[test]
+    fn restore_rejects_a_corrupted_backup() {
+        let dir = TestDir::new("backup-corrupted");
+        let mut searcher = FileSearch::create(&dir.index()).unwrap();
+        let first = dir.file("first.txt", "alpha");
+        let second = dir.file("second.txt", "bravo");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&first).unwrap();
+        writer.commit().unwrap();
+
+        let manifest = searcher.backup(&dir.0.join("backup")).unwrap();
+        let segment = manifest
+            .files
+            .iter()
+            .find(|file| file.path.ends_with(".idx"))
+            .unwrap();
+        let mut content = fs::read(dir.0.join("backup").join(&segment.path)).unwrap();
+        content[0] ^= 0xff;
+        fs::write(dir.0.join("backup").join(&segment.path), content).unwrap();
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&second).unwrap();
+        writer.commit().unwrap();
+
+        let error = searcher.restore(&dir.0.join("backup")).unwrap_err();
+
+        assert!(error.message.contains("is corrupted"), "{}", error.message);
+        assert_eq!(committed_paths(&searcher).len(), 2);
+    }
+
+    #[test]
+    fn substring_search_verifies_trigram_candidates() {
+        let dir = TestDir::new("substring-trigrams");
+        let searcher = FileSearch::builder(dir.index())
+            .trigrams(true)
+            .open()
+            .unwrap();
+        let exact = dir.file("exact.rs", "let value = foo.unwrap();");
+        let scattered = dir.file("scattered.rs", "food oo.unwrap()");
+        let other = dir.file("other.rs", "nothing here");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&exact).unwrap();
+        writer.add(&scattered).unwrap();
+        writer.add(&other).unwrap();
+        writer.commit().unwrap();
+
+        let reader = searcher
+            .open_read()
+            .unwrap()
+            .with_mode(FileSearchMode::Substring);
+        let field_trigrams = reader
+            .searcher()
+            .schema()
+            .get_field(TRIGRAMS_FIELD)
+            .unwrap();
+        let candidates = query::trigrams("foo.unwrap()", field_trigrams);
+
+        assert_eq!(reader.searcher().search(&*candidates, &Count).unwrap(), 2);
+
+        let page = reader.search_page("foo.unwrap()", 0, 10).unwrap();
+        assert_eq!(page_paths(&page), [exact.as_str()]);
+        assert_eq!(page.entries[0].matches[0].range, 12..24);
+
+        let page = reader.search_page("()", 0, 10).unwrap();
+        assert_eq!(page_paths(&page), [exact.as_str(), scattered.as_str()]);
+
+        let page = reader.search_page("o", 0, 10).unwrap();
+        assert_eq!(page.total, 3);
+
+        assert_eq!(reader.search_page("FOO", 0, 10).unwrap().total, 0);
+    }
+
+    #[test]
+    fn snapshots_keep_old_documents_until_deleted() {
+        let dir = TestDir::new("snapshots");
+        let searcher = FileSearch::create(&dir.index()).unwrap();
+        let first = dir.file("first.txt", "alpha");
+        let second = dir.file("second.txt", "bravo");
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&first).unwrap();
+        writer.commit().unwrap();
+
+        let snapshot = searcher.create_snapshot("v1").unwrap();
+        assert_eq!(snapshot.documents, 1);
+
+        let mut writer = searcher.open_write().unwrap();
+        writer.remove(&first).unwrap();
+        writer.add(&second).unwrap();
+        writer.commit().unwrap();
+
+        let at = searcher.open_read_at("v1").unwrap();
+        let listed: Vec<_> = at
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+
+        assert_eq!(listed, [first.as_str()]);
+        assert_eq!(at.search("alpha", None).unwrap().len(), 1);
+        assert!(at.search("bravo", None).unwrap().is_empty());
+        assert_eq!(committed_paths(&searcher), [second.as_str()]);
+        drop(at);
+
+        searcher.delete_snapshot("v1").unwrap();
+
+        let txn = searcher.db.begin_read().unwrap();
+        let states = txn.open_table(searcher.tables.snapshot_state()).unwrap();
+
+        assert_eq!(states.range(("v1", "")..).unwrap().count(), 0);
+        assert!(
+            searcher
+                .open_read()
+                .unwrap()
+                .snapshots()
+                .unwrap()
+                .is_empty()
+        );
+        assert!(searcher.open_read_at("v1").is_err());
+        assert!(!searcher.snapshot_path("v1").exists());
+        assert!(searcher.delete_snapshot("v1").is_err());
+    }
+
+    #[test]
+    fn federated_search_labels_local_hits_with_their_collection() {
+        let dir = TestDir::new("federated");
+        let mut searcher = FileSearch::create(&dir.index()).unwrap();
+        let code = dir.file("code.rs", "needle");
+
+        searcher.use_collection("code").unwrap();
+        let mut writer = searcher.open_write().unwrap();
+        writer.add(&code).unwrap();
+        writer.commit().unwrap();
+
+        let page = crate::federation::search_federated(&searcher, &[], "needle", 0, 10).unwrap();
+        let entry = &page.entries[0];
+        assert_eq!(entry.collection.as_deref(), Some("code"));
+        assert_eq!(
+            entry.index.as_deref(),
+            Some(dir.index().to_string_lossy().as_ref())
+        );
     }
 }
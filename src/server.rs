@@ -0,0 +1,262 @@
+use std::{collections::HashMap, sync::Arc, thread};
+
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server as HttpServer};
+
+use file_search::{Error, ErrorSource, FileSearch};
+
+use crate::service::{Service, ServiceError, ServiceResult};
+
+#[derive(Deserialize)]
+struct PathRequest {
+    path: String,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+const SESSION_HEADER: &str = "X-Session";
+const DEFAULT_SESSION: &str = "http";
+
+type HandlerResult = Result<(u16, String), (u16, String)>;
+
+pub struct Server {
+    service: Service,
+}
+
+impl Server {
+    pub fn new(searcher: FileSearch) -> Self {
+        Self {
+            service: Service::new(searcher),
+        }
+    }
+
+    pub fn listen(self, address: &str) -> Result<(), Error> {
+        let http = HttpServer::http(address).map_err(|error| Error {
+            source: ErrorSource::Io,
+            message: error.to_string(),
+        })?;
+        let http = Arc::new(http);
+        let server = Arc::new(self);
+        let workers = thread::available_parallelism().map_or(4, |count| count.get());
+
+        println!("Listening on http://{address}");
+
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                let http = Arc::clone(&http);
+                let server = Arc::clone(&server);
+
+                thread::spawn(move || {
+                    for request in http.incoming_requests() {
+                        server.handle_request(request);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let _ = handle.join();
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&self, mut request: Request) {
+        let url = request.url().to_owned();
+        let (route, query) = url.split_once('?').unwrap_or((&url, ""));
+        let query = Self::parse_query(query);
+        let session = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv(SESSION_HEADER))
+            .map_or(DEFAULT_SESSION.to_string(), |header| {
+                header.value.to_string()
+            });
+        let mut body = String::new();
+
+        let result = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => match (request.method(), route) {
+                (Method::Get, "/search") => self.handle_search(&query),
+                (Method::Get, "/list") => self.list(),
+                (Method::Get, "/stats") => self.stats(),
+                (Method::Post, "/add") => {
+                    Self::parse_path(&body).and_then(|path| self.add(&session, &path))
+                }
+                (Method::Post, "/remove") => {
+                    Self::parse_path(&body).and_then(|path| self.remove(&session, &path))
+                }
+                (Method::Post, "/commit") => self.commit(&session),
+                (Method::Post, "/rollback") => self.rollback(&session),
+                (
+                    _,
+                    "/search" | "/list" | "/stats" | "/add" | "/remove" | "/commit" | "/rollback",
+                ) => Err((405, "Method not allowed.".into())),
+                _ => Err((404, format!("Unknown endpoint '{route}'."))),
+            },
+            Err(error) => Err((400, format!("Cannot read request body. {error}"))),
+        };
+
+        let (status, body) = result.unwrap_or_else(|(status, error)| {
+            let body =
+                serde_json::to_string(&ErrorResponse { error }).unwrap_or_else(|_| "{}".into());
+            (status, body)
+        });
+        let header =
+            Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header);
+
+        if let Err(error) = request.respond(response) {
+            eprintln!("Failed to send response. {error}");
+        }
+    }
+
+    fn handle_search(&self, query: &HashMap<String, String>) -> HandlerResult {
+        let text = query
+            .get("q")
+            .filter(|text| !text.trim().is_empty())
+            .ok_or((400, "Missing query parameter 'q'.".to_string()))?;
+        let offset = Self::number_parameter(query, "offset")?;
+        let limit = Self::number_parameter(query, "limit")?;
+
+        Self::to_json(self.service.search(text, offset, limit))
+    }
+
+    fn list(&self) -> HandlerResult {
+        Self::to_json(self.service.list())
+    }
+
+    fn stats(&self) -> HandlerResult {
+        Self::to_json(self.service.stats())
+    }
+
+    fn add(&self, session: &str, path: &str) -> HandlerResult {
+        Self::to_empty(self.service.add(session, path))
+    }
+
+    fn remove(&self, session: &str, path: &str) -> HandlerResult {
+        Self::to_empty(self.service.remove(session, path))
+    }
+
+    fn commit(&self, session: &str) -> HandlerResult {
+        Self::to_json(self.service.commit(session))
+    }
+
+    fn rollback(&self, session: &str) -> HandlerResult {
+        Self::to_empty(self.service.rollback(session))
+    }
+
+    fn number_parameter(
+        query: &HashMap<String, String>,
+        name: &str,
+    ) -> Result<Option<usize>, (u16, String)> {
+        query
+            .get(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| (400, format!("Invalid {name} '{value}'.")))
+            })
+            .transpose()
+    }
+
+    fn parse_path(body: &str) -> Result<String, (u16, String)> {
+        serde_json::from_str::<PathRequest>(body)
+            .map(|request| request.path)
+            .map_err(|error| (400, format!("Invalid request body. {error}")))
+    }
+
+    fn to_json<T: Serialize>(result: ServiceResult<T>) -> HandlerResult {
+        let value = result.map_err(Self::to_status)?;
+
+        serde_json::to_string(&value)
+            .map(|json| (200, json))
+            .map_err(|error| (500, format!("Cannot serialize response. {error}")))
+    }
+
+    fn to_empty(result: ServiceResult<()>) -> HandlerResult {
+        result
+            .map(|_| (204, String::new()))
+            .map_err(Self::to_status)
+    }
+
+    fn to_status(error: ServiceError) -> (u16, String) {
+        let status = match error {
+            ServiceError::Invalid(_) => 400,
+            ServiceError::NotFound(_) => 404,
+            ServiceError::Conflict(_) => 409,
+            ServiceError::Internal(_) => 500,
+        };
+
+        (status, error.into_message())
+    }
+
+    fn parse_query(query: &str) -> HashMap<String, String> {
+        query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (Self::decode_component(key), Self::decode_component(value))
+            })
+            .collect()
+    }
+
+    fn decode_component(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut index = 0;
+
+        while index < bytes.len() {
+            match bytes[index] {
+                b'+' => decoded.push(b' '),
+                b'%' if index + 2 < bytes.len() => {
+                    let byte = std::str::from_utf8(&bytes[index + 1..index + 3])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+                    match byte {
+                        Some(byte) => {
+                            decoded.push(byte);
+                            index += 2;
+                        }
+                        _ => decoded.push(b'%'),
+                    }
+                }
+                byte => decoded.push(byte),
+            }
+
+            index += 1;
+        }
+
+        String::from_utf8_lossy(&decoded).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_component_handles_plus_and_percent_escapes() {
+        assert_eq!(Server::decode_component("hello+world"), "hello world");
+        assert_eq!(Server::decode_component("%E2%9C%93%20ok"), "✓ ok");
+        assert_eq!(Server::decode_component("100%"), "100%");
+        assert_eq!(Server::decode_component("%4"), "%4");
+        assert_eq!(Server::decode_component("%zz"), "%zz");
+    }
+
+    #[test]
+    fn parse_query_decodes_keys_and_values() {
+        let query = Server::parse_query("q=a%26b&limit=5&&flag");
+
+        assert_eq!(query.len(), 3);
+        assert_eq!(query["q"], "a&b");
+        assert_eq!(query["limit"], "5");
+        assert_eq!(query["flag"], "");
+    }
+}
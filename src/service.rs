@@ -0,0 +1,186 @@
+use std::{
+    fs,
+    sync::{Mutex, MutexGuard},
+};
+
+use file_search::{
+    Error, FileChangeSummary, FileDocumentEntry, FileSearch, FileSearchPage, FileSearchStats,
+    FileSearchWriteTransaction,
+};
+
+use crate::commands::DEFAULT_PAGE_SIZE;
+
+#[derive(Debug)]
+pub enum ServiceError {
+    Invalid(String),
+    NotFound(String),
+    Conflict(String),
+    Internal(String),
+}
+
+impl ServiceError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ServiceError::Invalid(_) => "invalid",
+            ServiceError::NotFound(_) => "not_found",
+            ServiceError::Conflict(_) => "conflict",
+            ServiceError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn into_message(self) -> String {
+        match self {
+            ServiceError::Invalid(message)
+            | ServiceError::NotFound(message)
+            | ServiceError::Conflict(message)
+            | ServiceError::Internal(message) => message,
+        }
+    }
+}
+
+pub type ServiceResult<T> = Result<T, ServiceError>;
+
+type SessionWriter = Option<(String, FileSearchWriteTransaction)>;
+
+pub struct Service {
+    searcher: FileSearch,
+    writer: Mutex<SessionWriter>,
+}
+
+impl Service {
+    pub fn new(searcher: FileSearch) -> Self {
+        Self {
+            searcher,
+            writer: Mutex::new(None),
+        }
+    }
+
+    pub fn search(
+        &self,
+        query: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> ServiceResult<FileSearchPage> {
+        self.searcher
+            .open_read()
+            .and_then(|reader| {
+                reader.search_page(
+                    query,
+                    offset.unwrap_or(0),
+                    limit.unwrap_or(DEFAULT_PAGE_SIZE),
+                )
+            })
+            .map_err(|error| ServiceError::Invalid(format!("Failed to search documents. {error}")))
+    }
+
+    pub fn list(&self) -> ServiceResult<Vec<FileDocumentEntry>> {
+        self.searcher
+            .open_read()
+            .and_then(|reader| reader.list())
+            .map_err(|error| ServiceError::Internal(format!("Cannot retrive documents. {error}")))
+    }
+
+    pub fn stats(&self) -> ServiceResult<FileSearchStats> {
+        self.searcher
+            .open_read()
+            .and_then(|reader| reader.stats())
+            .map_err(|error| ServiceError::Internal(format!("Cannot retrive stats. {error}")))
+    }
+
+    pub fn add(&self, session: &str, path: &str) -> ServiceResult<()> {
+        Self::resolve_file_path(path)?;
+        self.with_writer(session, |writer| writer.add(path))
+    }
+
+    pub fn remove(&self, session: &str, path: &str) -> ServiceResult<()> {
+        Self::resolve_file_path(path)?;
+        self.with_writer(session, |writer| writer.remove(path))
+    }
+
+    pub fn commit(&self, session: &str) -> ServiceResult<FileChangeSummary> {
+        self.finish_writer(session, |writer| {
+            writer
+                .commit()
+                .map_err(|error| ServiceError::Internal(format!("Failed to commit. {error}")))
+        })?
+        .ok_or_else(|| ServiceError::Conflict("No changes to commit.".into()))
+    }
+
+    pub fn rollback(&self, session: &str) -> ServiceResult<()> {
+        self.finish_writer(session, Self::rollback_writer)?
+            .ok_or_else(|| ServiceError::Conflict("No changes to rollback.".into()))
+    }
+
+    pub fn close(&self, session: &str) -> ServiceResult<()> {
+        self.finish_writer(session, Self::rollback_writer)
+            .map(|_| ())
+    }
+
+    fn with_writer<F>(&self, session: &str, f: F) -> ServiceResult<()>
+    where
+        F: FnOnce(&mut FileSearchWriteTransaction) -> Result<(), Error>,
+    {
+        let mut guard = self.lock_writer()?;
+
+        match guard.as_ref() {
+            Some((owner, _)) if owner != session => {
+                return Err(ServiceError::Conflict(
+                    "Another session has uncommitted changes. Try again once it commits or rolls back."
+                        .into(),
+                ));
+            }
+            Some(_) => {}
+            _ => {
+                let writer = self.searcher.open_write().map_err(|error| {
+                    ServiceError::Internal(format!("Unable to start write session. {error}"))
+                })?;
+                *guard = Some((session.into(), writer));
+            }
+        }
+
+        match guard.as_mut() {
+            Some((_, writer)) => f(writer).map_err(|error| {
+                ServiceError::Internal(format!("Failed to change index. {error}"))
+            }),
+            _ => Err(ServiceError::Internal(
+                "Unable to start write session.".into(),
+            )),
+        }
+    }
+
+    fn finish_writer<F, T>(&self, session: &str, f: F) -> ServiceResult<Option<T>>
+    where
+        F: FnOnce(FileSearchWriteTransaction) -> ServiceResult<T>,
+    {
+        let mut guard = self.lock_writer()?;
+
+        match guard.take_if(|(owner, _)| owner == session) {
+            Some((_, writer)) => f(writer).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn rollback_writer(writer: FileSearchWriteTransaction) -> ServiceResult<()> {
+        writer
+            .rollback()
+            .map_err(|error| ServiceError::Internal(format!("Failed to rollback. {error}")))
+    }
+
+    fn lock_writer(&self) -> ServiceResult<MutexGuard<'_, SessionWriter>> {
+        self.writer
+            .lock()
+            .map_err(|_| ServiceError::Internal("Write session is poisoned.".into()))
+    }
+
+    fn resolve_file_path(path: &str) -> ServiceResult<()> {
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => Ok(()),
+            Ok(_) => Err(ServiceError::Invalid(format!(
+                "The path '{path}' is not a file."
+            ))),
+            Err(error) => Err(ServiceError::NotFound(format!(
+                "Failed to access file '{path}'. {error}"
+            ))),
+        }
+    }
+}
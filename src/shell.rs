@@ -1,70 +1,304 @@
 use std::{
     fs,
-    io::{self, BufRead},
+    io::{self, BufRead, IsTerminal},
+    path::Path,
 };
 
+use file_search::{
+    Error, ErrorSource, FederatedIndex, FileSearch, FileSearchMode, FileSearchWriteTransaction,
+    FileSnippetOptions, search_federated,
+};
+use rustyline::{Editor, error::ReadlineError, history::DefaultHistory};
+
 use crate::{
-    error::Error,
-    search::{FileSearch, FileSearchWriteTransaction},
+    args::Args,
+    commands::DEFAULT_PAGE_SIZE,
+    completion::ShellHelper,
+    output::{self, Format},
 };
 
+const HISTORY_FILENAME: &str = "history.txt";
+
+macro_rules! fail {
+    ($shell:ident, $($arg:tt)*) => {{
+        eprintln!($($arg)*);
+        $shell.failed = true;
+    }};
+}
+
 pub struct Shell {
     searcher: FileSearch,
     writer: Option<FileSearchWriteTransaction>,
+    federated: Vec<FederatedIndex>,
+    failed: bool,
+    format: Format,
 }
 
 impl Shell {
-    pub fn new(searcher: FileSearch) -> Self {
+    pub fn new(searcher: FileSearch, format: Format) -> Self {
         Self {
             searcher,
             writer: None,
+            federated: Vec::new(),
+            failed: false,
+            format,
         }
     }
 
+    pub fn federate(&mut self, path: &str) -> Result<(), Error> {
+        self.federated.push(FederatedIndex::open(path)?);
+        Ok(())
+    }
+
     pub fn watch(&mut self) {
         self.handle_help_command();
 
+        if !io::stdin().is_terminal() {
+            return self.watch_lines();
+        }
+
+        match Editor::new() {
+            Ok(editor) => self.watch_editor(editor),
+            Err(error) => {
+                eprintln!("Unable to start line editor. {error}");
+                self.watch_lines();
+            }
+        }
+    }
+
+    fn watch_lines(&mut self) {
         for line in io::stdin().lock().lines() {
             match line {
                 Ok(line) => {
-                    if line.trim().is_empty() {
-                        continue;
+                    if !self.handle_line(&line) {
+                        break;
                     }
+                }
+                Err(error) => eprintln!("{error}"),
+            }
+        }
+    }
 
-                    let mut parts = line.splitn(2, ' ');
+    fn watch_editor(&mut self, mut editor: Editor<ShellHelper, DefaultHistory>) {
+        let history = self.searcher.root().join(HISTORY_FILENAME);
 
-                    if let Some(command) = parts.next() {
-                        let arg = parts.next();
+        if history.exists()
+            && let Err(error) = editor.load_history(&history)
+        {
+            eprintln!("Unable to load history. {error}");
+        }
 
-                        if !self.handle_command(command, arg) {
-                            break;
-                        }
+        editor.set_helper(Some(ShellHelper::new()));
+
+        loop {
+            if let Some(helper) = editor.helper_mut() {
+                let added = self
+                    .writer
+                    .as_ref()
+                    .map(|writer| writer.changes().added)
+                    .unwrap_or_default();
+
+                helper.set_indexed(self.searcher.open_read().ok(), added);
+            }
+
+            let line = editor.readline("> ");
+
+            if let Some(helper) = editor.helper_mut() {
+                helper.set_indexed(None, Vec::new());
+            }
+
+            match line {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        let _ = editor.add_history_entry(line.as_str());
+                    }
+
+                    if !self.handle_line(&line) {
+                        break;
                     }
                 }
-                Err(error) => eprintln!("{error}"),
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(error) => {
+                    eprintln!("{error}");
+                    break;
+                }
+            }
+        }
+
+        if let Err(error) = editor.save_history(&history) {
+            eprintln!("Unable to save history. {error}");
+        }
+    }
+
+    pub fn run_script(&mut self, path: &str) -> Result<(), Error> {
+        self.source(path)?;
+        self.close();
+        Ok(())
+    }
+
+    fn source(&mut self, path: &str) -> Result<bool, Error> {
+        let file = fs::File::open(path)?;
+
+        for (index, line) in io::BufReader::new(file).lines().enumerate() {
+            let line = line?;
+
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            self.failed = false;
+            let running = self.handle_line(&line);
+
+            if self.failed {
+                self.close();
+
+                return Err(Error {
+                    source: ErrorSource::Search,
+                    message: format!("Command failed at line {} of '{path}'.", index + 1),
+                });
+            }
+
+            if !running {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn close(&mut self) {
+        if self.writer.is_some() {
+            self.handle_rollback_command();
+        }
+    }
+
+    fn handle_line(&mut self, line: &str) -> bool {
+        let tokens = match shlex::split(line) {
+            Some(tokens) => tokens,
+            _ => {
+                fail!(self, "Unbalanced quotes or trailing escape in: {line}");
+                return true;
             }
+        };
+
+        match tokens.split_first() {
+            Some((name, tokens)) => self.handle_command(name, tokens),
+            _ => true,
         }
     }
 
-    fn handle_command(&mut self, name: &str, arg: Option<&str>) -> bool {
-        let (name, arg) = (name.trim(), arg.map(str::trim));
+    fn handle_command(&mut self, name: &str, tokens: &[String]) -> bool {
+        let (flags, options): (&[&str], &[&str]) = match name {
+            "add" | "remove" => (&["-r"], &[]),
+            "list" => (&[], &["--at", "--format"]),
+            "search" => (
+                &["--federated", "--committed"],
+                &[
+                    "--limit",
+                    "--offset",
+                    "--cursor",
+                    "--at",
+                    "--in",
+                    "--format",
+                    "--snippets",
+                    "--snippet-length",
+                    "--fuzzy",
+                ],
+            ),
+            "grep" => (
+                &["--committed"],
+                &[
+                    "--limit",
+                    "--offset",
+                    "--at",
+                    "--format",
+                    "--snippets",
+                    "--snippet-length",
+                ],
+            ),
+            "regex" => (
+                &["--content", "--committed"],
+                &[
+                    "--limit",
+                    "--offset",
+                    "--at",
+                    "--format",
+                    "--snippets",
+                    "--snippet-length",
+                ],
+            ),
+            _ => (&[], &[]),
+        };
+        let parse = match name {
+            "search" | "grep" | "regex" => Args::parse_query,
+            _ => Args::parse,
+        };
+        let args = match parse(tokens, flags, options) {
+            Ok(args) => args,
+            Err(error) => {
+                fail!(self, "{error}");
+                return true;
+            }
+        };
 
-        match (name, arg) {
-            ("help", None) => self.handle_help_command(),
-            ("clear", None) => self.handle_clear_command(),
-            ("list", None) => self.handle_list_command(),
-            ("commit", None) => self.handle_commit_command(),
-            ("rollback", None) => self.handle_rollback_command(),
-            ("exit", None) => {
-                self.handle_rollback_command();
+        match (name, args.values()) {
+            ("help", []) => self.handle_help_command(),
+            ("clear", []) => self.handle_clear_command(),
+            ("list", []) => {
+                if let Some(format) = self.output_format(&args) {
+                    self.handle_list_command(args.option("--at"), format)
+                }
+            }
+            ("format", []) => println!("Output format is {}.", self.format),
+            ("format", [name]) => self.handle_format_command(name),
+            ("snapshots", []) => self.handle_snapshots_command(),
+            ("collections", []) => self.handle_collections_command(),
+            ("federation", []) => self.handle_federation_command(),
+            ("status", []) => self.handle_status_command(),
+            ("commit", []) => self.handle_commit_command(),
+            ("rollback", []) => self.handle_rollback_command(),
+            ("exit", []) => {
+                self.close();
                 return false;
             }
-            ("add", Some(path)) => self.handle_add_command(path),
-            ("remove", Some(path)) => self.handle_remove_command(path),
-            ("search", Some(query)) => self.handle_search_command(query),
+            ("source", [path]) => return self.handle_source_command(path),
+            ("add", [_, ..]) => self.handle_add_command(args.values(), args.flag("-r")),
+            ("remove", [_, ..]) => self.handle_remove_command(args.values(), args.flag("-r")),
+            ("search", values) if !values.is_empty() || args.option("--cursor").is_some() => {
+                if let Some(format) = self.output_format(&args) {
+                    self.handle_search_command(&args, format, FileSearchMode::Query)
+                }
+            }
+            ("grep", [_, ..]) => {
+                if let Some(format) = self.output_format(&args) {
+                    self.handle_search_command(&args, format, FileSearchMode::Substring)
+                }
+            }
+            ("regex", [_, ..]) => {
+                let mode = if args.flag("--content") {
+                    FileSearchMode::RegexContent
+                } else {
+                    FileSearchMode::Regex
+                };
+
+                if let Some(format) = self.output_format(&args) {
+                    self.handle_search_command(&args, format, mode)
+                }
+            }
+            ("use", [name]) => self.handle_use_command(name),
+            ("federate", [path]) => self.handle_federate_command(path),
+            ("export", [path]) => self.handle_export_command(path),
+            ("import", [path]) => self.handle_import_command(path),
+            ("backup", [path]) => self.handle_backup_command(path),
+            ("restore", [path]) => self.handle_restore_command(path),
+            ("snapshot", [name]) => self.handle_snapshot_command(name),
+            ("delete-snapshot", [name]) => self.handle_delete_snapshot_command(name),
+            ("savepoint", [name]) => self.handle_savepoint_command(name),
+            ("rollback-to", [name]) => self.handle_rollback_to_command(name),
             _ => {
-                eprintln!("Unknown command: {name} {}", arg.unwrap_or_default());
-                eprintln!("Type 'help' to see available commands.");
+                fail!(self, "Unknown command: {name} {}", tokens.join(" "));
+                fail!(self, "Type 'help' to see available commands.");
             }
         }
 
@@ -73,63 +307,186 @@ impl Shell {
 
     fn handle_help_command(&mut self) {
         println!("Commands:");
-        println!("  help             Show this help message");
-        println!("  list             Show all documents");
-        println!("  add <path>       Add a new document");
-        println!("  remove <path>    Remove an existing document");
-        println!("  clear            Remove all documents from index");
-        println!("  commit           Commit pending changes");
-        println!("  rollback         Undo pending changes");
-        println!("  search <query>   Search documents");
-        println!("  exit             Exit the program");
+        println!("  help                        Show this help message");
+        println!("  list                        Show all documents");
+        println!("  status                      Show pending uncommitted changes");
+        println!("  add <path>...               Add new documents");
+        println!("  add -r <dir>...             Add all files below directories");
+        println!("  remove <path>...            Remove existing documents");
+        println!("  remove -r <dir>...          Remove all files below directories");
+        println!("  clear                       Remove all documents from index");
+        println!("  commit                      Commit pending changes and print a summary");
+        println!("  rollback                    Undo pending changes");
+        println!("  savepoint <name>            Mark the current point of the transaction");
+        println!("  rollback-to <name>          Undo pending changes made after a savepoint");
+        println!("  search <query>              Search documents");
+        println!("  search --limit <n> <query>  Search documents returning pages of n results");
+        println!("  search --offset <n> <query> Search documents skipping the first n results");
+        println!("  search --cursor <cursor>    Show the next page of an earlier search");
+        println!("  search --snippets <n> ...   Show up to n context snippets per result");
+        println!("  search --snippet-length <n> Limit context snippets to n bytes");
+        println!("  search --fuzzy <n> <query>  Search documents allowing n typos per term");
+        println!("  regex '<pattern>'           Search indexed words matching a pattern");
+        println!("  regex --content '<pattern>' Search file contents matching a pattern");
+        println!("  grep '<text>'               Search file contents containing the exact text");
+        println!("  format [<name>]             Show or set the output format");
+        println!("  search --format <name> ...  Search documents printing a given format");
+        println!("  list --format <name>        Show all documents printing a given format");
+        println!("  snapshot <name>             Tag the committed state as a named snapshot");
+        println!("  snapshots                   Show all snapshots");
+        println!("  delete-snapshot <name>      Delete a snapshot");
+        println!("  list --at <name>            Show all documents of a snapshot");
+        println!("  search --at <name> <query>  Search documents of a snapshot");
+        println!("  search --committed <query>  Search documents ignoring pending changes");
+        println!("  search --in <a,b> <query>   Search documents of several collections");
+        println!("  use <collection>            Switch to a collection, creating it if needed");
+        println!("  collections                 Show all collections");
+        println!("  federate <dir>              Attach another index directory for searching");
+        println!("  federation                  Show attached index directories");
+        println!("  search --federated <query>  Search this and all attached indexes");
+        println!(
+            "  export <file>               Write the collection's documents to a JSON Lines file"
+        );
+        println!("  import <file>               Load documents from a JSON Lines file");
+        println!(
+            "  backup <dir>                Copy the collection's committed index into a directory"
+        );
+        println!(
+            "  restore <dir>               Verify a backup and replace the collection with it"
+        );
+        println!(
+            "  source <file>               Run commands from a file, stopping at the first failure"
+        );
+        println!("  exit                        Exit the program");
         println!();
+        println!("Arguments are split like a POSIX shell, so quote paths with spaces.");
+        println!("Use -- to pass a query starting with '-', e.g. search -- -foo bar");
+        println!("Output formats: human, json, ndjson, csv.");
+        println!();
+    }
+
+    fn handle_format_command(&mut self, name: &str) {
+        match Format::parse(name) {
+            Ok(format) => {
+                self.format = format;
+                println!("Output format set to {format}.");
+            }
+            Err(error) => fail!(self, "{error}"),
+        }
+    }
+
+    fn handle_source_command(&mut self, path: &str) -> bool {
+        match self.source(path) {
+            Ok(running) => running,
+            Err(error) => {
+                fail!(self, "Failed to run script. {error}");
+                true
+            }
+        }
     }
 
     fn handle_clear_command(&mut self) {
         if self.writer.is_some() {
-            eprintln!("You have uncommitted changes. Please commit or rollback before clearing.");
+            fail!(
+                self,
+                "You have uncommitted changes. Please commit or rollback before clearing."
+            );
             return;
         }
 
         match self.searcher.open_write() {
             Ok(writer) => match writer.clear() {
-                Ok(()) => {
-                    println!("Index and state cleared successfully.");
+                Ok(summary) => {
+                    println!(
+                        "Index and state cleared successfully. Removed {} documents.",
+                        summary.removed.len()
+                    );
 
                     if let Err(error) = self.searcher.compact() {
-                        eprintln!("Unable to compact database. {error}")
+                        fail!(self, "Unable to compact database. {error}")
                     }
                 }
-                Err(error) => eprintln!("Failed to clear documents. {error}"),
+                Err(error) => fail!(self, "Failed to clear documents. {error}"),
             },
-            Err(error) => eprintln!("Unable to start write session. {error}"),
+            Err(error) => fail!(self, "Unable to start write session. {error}"),
         }
     }
 
-    fn handle_list_command(&mut self) {
-        match self.searcher.open_read() {
+    fn handle_list_command(&mut self, snapshot: Option<&str>, format: Format) {
+        let reader = match snapshot {
+            Some(snapshot) => self.searcher.open_read_at(snapshot),
+            _ => self.searcher.open_read(),
+        };
+
+        match reader {
             Ok(trx) => match trx.list() {
-                Ok(entries) => match serde_json::to_string(&entries) {
-                    Ok(json) => println!("{json}"),
-                    Err(error) => eprintln!("Cannot serialize found entries. {error}"),
-                },
-                Err(error) => eprintln!("Cannot retrive documents. {error}"),
+                Ok(entries) => {
+                    if let Err(error) = output::print(&entries, format) {
+                        fail!(self, "Cannot print found entries. {error}");
+                    }
+                }
+                Err(error) => fail!(self, "Cannot retrive documents. {error}"),
             },
-            Err(err) => eprintln!("Unable to start read session. {err}"),
+            Err(err) => fail!(self, "Unable to start read session. {err}"),
+        }
+    }
+
+    fn handle_status_command(&mut self) {
+        match self.writer {
+            Some(ref writer) => {
+                let summary = writer.changes();
+                let staged = [
+                    ("added", &summary.added),
+                    ("updated", &summary.updated),
+                    ("touched", &summary.touched),
+                    ("removed", &summary.removed),
+                ];
+
+                println!("On collection {}.", self.searcher.collection());
+                println!("Transaction open for {}s.", writer.elapsed().as_secs());
+
+                if staged.iter().all(|(_, paths)| paths.is_empty()) {
+                    println!("No changes staged.");
+                    return;
+                }
+
+                println!("Changes to be committed:");
+
+                for (label, paths) in staged {
+                    for path in paths {
+                        println!("  {:<9}{path}", format!("{label}:"));
+                    }
+                }
+
+                let savepoints = writer.savepoints();
+
+                if !savepoints.is_empty() {
+                    println!("Savepoints: {}", savepoints.join(", "));
+                }
+            }
+            _ => {
+                println!("On collection {}.", self.searcher.collection());
+                println!("No pending changes.");
+            }
         }
     }
 
     fn handle_commit_command(&mut self) {
         match self.writer.take() {
             Some(writer) => match writer.commit() {
-                Ok(()) => {
+                Ok(summary) => {
+                    match serde_json::to_string(&summary) {
+                        Ok(json) => println!("{json}"),
+                        Err(error) => fail!(self, "Cannot serialize commit summary. {error}"),
+                    }
+
                     if let Err(error) = self.searcher.compact() {
-                        eprintln!("Unable to compact database. {error}")
+                        fail!(self, "Unable to compact database. {error}")
                     }
                 }
-                Err(error) => eprintln!("Failed to commit. {error}"),
+                Err(error) => fail!(self, "Failed to commit. {error}"),
             },
-            _ => eprintln!("No changes to commit."),
+            _ => fail!(self, "No changes to commit."),
         }
     }
 
@@ -138,42 +495,340 @@ impl Shell {
             Some(writer) => match writer.rollback() {
                 Ok(()) => {
                     if let Err(error) = self.searcher.compact() {
-                        eprintln!("Unable to compact database. {error}")
+                        fail!(self, "Unable to compact database. {error}")
                     }
                 }
-                Err(error) => eprintln!("Failed to rollback. {error}"),
+                Err(error) => fail!(self, "Failed to rollback. {error}"),
+            },
+            _ => fail!(self, "No changes to rollback."),
+        }
+    }
+
+    fn handle_savepoint_command(&mut self, name: &str) {
+        match self.get_or_create_writer() {
+            Ok(writer) => {
+                if let Err(error) = writer.savepoint(name) {
+                    fail!(self, "Failed to create savepoint. {error}");
+                }
+            }
+            Err(error) => fail!(self, "Unable to start write session. {error}"),
+        }
+    }
+
+    fn handle_rollback_to_command(&mut self, name: &str) {
+        match self.writer {
+            Some(ref mut writer) => {
+                if let Err(error) = writer.rollback_to(name) {
+                    fail!(self, "Failed to rollback to savepoint. {error}");
+                }
+            }
+            _ => fail!(self, "No changes to rollback."),
+        }
+    }
+
+    fn handle_add_command(&mut self, paths: &[String], recursive: bool) {
+        for path in self.resolve_file_paths(paths, recursive) {
+            self.with_writer(|writer| writer.add(&path));
+        }
+    }
+
+    fn handle_remove_command(&mut self, paths: &[String], recursive: bool) {
+        for path in self.resolve_file_paths(paths, recursive) {
+            self.with_writer(|writer| writer.remove(&path));
+        }
+    }
+
+    fn handle_search_command(&mut self, args: &Args, format: Format, mode: FileSearchMode) {
+        let query = args.values().join(" ");
+        let (Ok(limit), Ok(offset), Ok(count), Ok(max_length), Ok(fuzzy)) = (
+            self.number_option(args, "--limit"),
+            self.number_option(args, "--offset"),
+            self.number_option(args, "--snippets"),
+            self.number_option(args, "--snippet-length"),
+            self.number_option(args, "--fuzzy"),
+        ) else {
+            return;
+        };
+        let defaults = FileSnippetOptions::default();
+        let snippets = FileSnippetOptions {
+            count: count.unwrap_or(defaults.count),
+            max_length: max_length.unwrap_or(defaults.max_length),
+        };
+
+        let (offset, limit) = (offset.unwrap_or(0), limit.unwrap_or(DEFAULT_PAGE_SIZE));
+        let merged = args.flag("--federated") || args.option("--in").is_some();
+        let unsupported = [
+            "--cursor",
+            "--at",
+            "--snippets",
+            "--snippet-length",
+            "--fuzzy",
+        ]
+        .into_iter()
+        .find(|name| args.option(name).is_some());
+
+        if let (true, Some(name)) = (merged, unsupported) {
+            fail!(self, "{name} cannot be combined with --federated or --in.");
+            return;
+        }
+
+        if args.flag("--federated") {
+            return self.handle_search_federated_command(&query, offset, limit, format);
+        }
+
+        if let Some(collections) = args.option("--in") {
+            let collections: Vec<_> = collections.split(',').collect();
+            return self.handle_search_collections_command(
+                &collections,
+                &query,
+                (offset, limit),
+                format,
+            );
+        }
+
+        let reader = match args.option("--at") {
+            Some(snapshot) => self.searcher.open_read_at(snapshot),
+            _ => self.searcher.open_read(),
+        }
+        .map(|reader| {
+            reader
+                .with_snippets(snippets)
+                .with_fuzzy(fuzzy.map(|distance| u8::try_from(distance).unwrap_or(u8::MAX)))
+                .with_mode(mode)
+        });
+        let page = match (&self.writer, args.option("--cursor")) {
+            (_, Some(cursor)) => reader.and_then(|reader| reader.search_cursor(cursor)),
+            (Some(writer), None) if !args.flag("--committed") && args.option("--at").is_none() => {
+                reader.and_then(|reader| writer.search_page(&reader, &query, offset, limit))
+            }
+            _ => reader.and_then(|reader| reader.search_page(&query, offset, limit)),
+        };
+
+        match page {
+            Ok(page) => {
+                if let Some(warning) = &page.warning {
+                    eprintln!("{warning}");
+                }
+
+                if let Err(error) = output::print_page(&page, format) {
+                    fail!(self, "Cannot print found entries. {error}");
+                }
+            }
+            Err(error) => fail!(self, "Failed to search documents. {error}"),
+        }
+    }
+
+    fn handle_export_command(&mut self, path: &str) {
+        let result = fs::File::create(path)
+            .map_err(Error::from)
+            .and_then(|file| {
+                self.searcher
+                    .open_read()
+                    .and_then(|reader| reader.export(io::BufWriter::new(file)))
+            });
+
+        match result {
+            Ok(count) => println!(
+                "Exported {count} documents of collection {} to '{path}'.",
+                self.searcher.collection()
+            ),
+            Err(error) => fail!(self, "Failed to export documents. {error}"),
+        }
+    }
+
+    fn handle_import_command(&mut self, path: &str) {
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(error) => {
+                fail!(self, "Failed to access file '{path}'. {error}");
+                return;
+            }
+        };
+
+        self.with_writer(|writer| {
+            let count = writer.import(io::BufReader::new(file))?;
+            println!("Imported {count} documents from '{path}'.");
+            Ok(())
+        });
+    }
+
+    fn handle_backup_command(&mut self, path: &str) {
+        match self.searcher.backup(Path::new(path)) {
+            Ok(manifest) => println!(
+                "Backed up {} documents of collection {} to '{path}'.",
+                manifest.documents, manifest.collection
+            ),
+            Err(error) => fail!(self, "Failed to back up index. {error}"),
+        }
+    }
+
+    fn handle_restore_command(&mut self, path: &str) {
+        if self.writer.is_some() {
+            fail!(
+                self,
+                "You have uncommitted changes. Please commit or rollback before restoring."
+            );
+            return;
+        }
+
+        match self.searcher.restore(Path::new(path)) {
+            Ok(manifest) => println!(
+                "Restored {} documents of collection {} from '{path}'.",
+                manifest.documents, manifest.collection
+            ),
+            Err(error) => fail!(self, "Failed to restore index. {error}"),
+        }
+    }
+
+    fn handle_search_collections_command(
+        &mut self,
+        collections: &[&str],
+        query: &str,
+        (offset, limit): (usize, usize),
+        format: Format,
+    ) {
+        match self
+            .searcher
+            .search_collections(collections, query, offset, limit)
+        {
+            Ok(page) => {
+                if let Err(error) = output::print_page(&page, format) {
+                    fail!(self, "Cannot print found entries. {error}");
+                }
+            }
+            Err(error) => fail!(self, "Failed to search documents. {error}"),
+        }
+    }
+
+    fn handle_search_federated_command(
+        &mut self,
+        query: &str,
+        offset: usize,
+        limit: usize,
+        format: Format,
+    ) {
+        match search_federated(&self.searcher, &self.federated, query, offset, limit) {
+            Ok(page) => {
+                if let Err(error) = output::print_page(&page, format) {
+                    fail!(self, "Cannot print found entries. {error}");
+                }
+            }
+            Err(error) => fail!(self, "Failed to search documents. {error}"),
+        }
+    }
+
+    fn handle_federate_command(&mut self, path: &str) {
+        if self.federated.iter().any(|member| member.name() == path) {
+            fail!(self, "The index '{path}' is already attached.");
+            return;
+        }
+
+        match self.federate(path) {
+            Ok(()) => println!("Attached index '{path}'."),
+            Err(error) => fail!(self, "Failed to attach index. {error}"),
+        }
+    }
+
+    fn handle_federation_command(&mut self) {
+        let names: Vec<_> = self.federated.iter().map(|member| member.name()).collect();
+
+        match serde_json::to_string(&names) {
+            Ok(json) => println!("{json}"),
+            Err(error) => fail!(self, "Cannot serialize attached indexes. {error}"),
+        }
+    }
+
+    fn handle_use_command(&mut self, name: &str) {
+        if self.writer.is_some() {
+            fail!(
+                self,
+                "You have uncommitted changes. Please commit or rollback before switching collections."
+            );
+            return;
+        }
+
+        match self.searcher.use_collection(name) {
+            Ok(()) => println!("Using collection '{name}'."),
+            Err(error) => fail!(self, "Failed to switch collection. {error}"),
+        }
+    }
+
+    fn handle_collections_command(&mut self) {
+        match self.searcher.collections() {
+            Ok(collections) => match serde_json::to_string(&collections) {
+                Ok(json) => println!("{json}"),
+                Err(error) => fail!(self, "Cannot serialize collections. {error}"),
             },
-            _ => eprintln!("No changes to rollback."),
+            Err(error) => fail!(self, "Cannot retrive collections. {error}"),
         }
     }
 
-    fn handle_add_command(&mut self, path: &str) {
-        if let Some(path) = Self::resolve_file_path(path) {
-            self.with_writer(|writer| writer.add(path));
+    fn handle_snapshot_command(&mut self, name: &str) {
+        if self.writer.is_some() {
+            fail!(
+                self,
+                "You have uncommitted changes. Please commit or rollback before creating a snapshot."
+            );
+            return;
+        }
+
+        match self.searcher.create_snapshot(name) {
+            Ok(snapshot) => match serde_json::to_string(&snapshot) {
+                Ok(json) => println!("{json}"),
+                Err(error) => fail!(self, "Cannot serialize snapshot. {error}"),
+            },
+            Err(error) => fail!(self, "Failed to create snapshot. {error}"),
         }
     }
 
-    fn handle_remove_command(&mut self, path: &str) {
-        if let Some(path) = Self::resolve_file_path(path) {
-            self.with_writer(|writer| writer.remove(path));
+    fn handle_snapshots_command(&mut self) {
+        match self
+            .searcher
+            .open_read()
+            .and_then(|reader| reader.snapshots())
+        {
+            Ok(snapshots) => match serde_json::to_string(&snapshots) {
+                Ok(json) => println!("{json}"),
+                Err(error) => fail!(self, "Cannot serialize snapshots. {error}"),
+            },
+            Err(error) => fail!(self, "Cannot retrive snapshots. {error}"),
+        }
+    }
+
+    fn handle_delete_snapshot_command(&mut self, name: &str) {
+        if self.writer.is_some() {
+            fail!(
+                self,
+                "You have uncommitted changes. Please commit or rollback before deleting a snapshot."
+            );
+            return;
+        }
+
+        match self.searcher.delete_snapshot(name) {
+            Ok(()) => println!("Snapshot '{name}' deleted."),
+            Err(error) => fail!(self, "Failed to delete snapshot. {error}"),
         }
     }
 
-    fn handle_search_command(&mut self, query: &str) {
-        if self.writer.is_none() {
-            match self
-                .searcher
-                .open_read()
-                .and_then(|reader| reader.search(query, None))
-            {
-                Ok(entries) => match serde_json::to_string(&entries) {
-                    Ok(json) => println!("{json}"),
-                    Err(error) => eprintln!("Cannot serialize found entries. {error}"),
-                },
-                Err(error) => eprintln!("Failed to search documents. {error}"),
+    fn output_format(&mut self, args: &Args) -> Option<Format> {
+        match args.option("--format").map(Format::parse) {
+            Some(Ok(format)) => Some(format),
+            Some(Err(error)) => {
+                fail!(self, "{error}");
+                None
             }
-        } else {
-            eprintln!("You have uncommitted changes. Please commit or rollback before searching.")
+            _ => Some(self.format),
+        }
+    }
+
+    fn number_option(&mut self, args: &Args, name: &str) -> Result<Option<usize>, ()> {
+        match args.option(name).map(str::parse::<usize>) {
+            Some(Ok(value)) => Ok(Some(value)),
+            Some(Err(error)) => {
+                fail!(self, "Invalid value for option '{name}'. {error}");
+                Err(())
+            }
+            _ => Ok(None),
         }
     }
 
@@ -195,24 +850,57 @@ impl Shell {
         match self.get_or_create_writer() {
             Ok(writer) => {
                 if let Err(error) = f(writer) {
-                    eprintln!("Failed to change index. {error}");
+                    fail!(self, "Failed to change index. {error}");
                 }
             }
-            Err(err) => eprintln!("Unable to start write session. {err}"),
+            Err(err) => fail!(self, "Unable to start write session. {err}"),
+        }
+    }
+
+    fn resolve_file_paths(&mut self, paths: &[String], recursive: bool) -> Vec<String> {
+        let mut files = Vec::new();
+
+        for path in paths {
+            self.collect_file_paths(Path::new(path), recursive, &mut files);
         }
+
+        files
     }
 
-    fn resolve_file_path(path: &str) -> Option<&str> {
+    fn collect_file_paths(&mut self, path: &Path, recursive: bool, files: &mut Vec<String>) {
         match fs::metadata(path) {
-            Ok(metadata) if metadata.is_file() => Some(path),
-            Ok(_) => {
-                eprintln!("The path '{path}' is not a file.");
-                None
-            }
-            Err(error) => {
-                eprintln!("Failed to access file '{path}'. {error}");
-                None
+            Ok(metadata) if metadata.is_file() => files.push(path.to_string_lossy().into_owned()),
+            Ok(metadata) if metadata.is_dir() && recursive => {
+                let entries = fs::read_dir(path).and_then(|entries| {
+                    entries
+                        .map(|entry| entry.map(|entry| entry.path()))
+                        .collect::<Result<Vec<_>, _>>()
+                });
+
+                match entries {
+                    Ok(mut entries) => {
+                        entries.sort();
+
+                        for entry in entries {
+                            self.collect_file_paths(&entry, recursive, files);
+                        }
+                    }
+                    Err(error) => {
+                        fail!(
+                            self,
+                            "Failed to read directory '{}'. {error}",
+                            path.display()
+                        )
+                    }
+                }
             }
+            Ok(metadata) if metadata.is_dir() => fail!(
+                self,
+                "The path '{}' is a directory. Use -r to include its files.",
+                path.display()
+            ),
+            Ok(_) => fail!(self, "The path '{}' is not a file.", path.display()),
+            Err(error) => fail!(self, "Failed to access file '{}'. {error}", path.display()),
         }
     }
 }